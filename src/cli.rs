@@ -1,16 +1,57 @@
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
+use std::path::PathBuf;
+use crate::ui::colors::ColorChoice;
+use crate::ui::display::OutputFormat;
 
-use crate::commands::{add::AddCommand, remove::RemoveCommand, open::OpenCommand};
+use crate::commands::{add::AddCommand, remove::RemoveCommand, open::OpenCommand, status::StatusCommand, clone::CloneCommand, completions::{CompletionsCommand, CompleteCommand}, undo::{UndoCommand, RestoreCommand}, snapshots::SnapshotsCommand, archive::{ArchiveCommand, UnarchiveCommand}, doctor::DoctorCommand, adopt::AdoptCommand, stack::{StackCommand, RestackCommand}, env::EnvCommand, sparse::SparseCommand};
 
 #[derive(Parser)]
 #[command(name = "wt")]
 #[command(about = "Git Worktree CLI Tool - Easily manage Git worktrees with interactive CLI prompts")]
 #[command(version = "1.0.0")]
 pub struct Cli {
+    #[command(flatten)]
+    pub global: GlobalOpts,
+
     #[command(subcommand)]
     pub command: Commands,
 }
 
+#[derive(Debug, Args)]
+pub struct GlobalOpts {
+    /// Run as if wt was started in <DIR> instead of the current directory
+    #[arg(short = 'C', value_name = "DIR", global = true)]
+    pub directory: Option<PathBuf>,
+
+    /// Discover the repository from <PATH> instead of the current directory
+    #[arg(long = "repo", value_name = "PATH", env = "WT_REPO", global = true)]
+    pub repo: Option<PathBuf>,
+
+    /// Output format: human-readable text or a single JSON document per command
+    #[arg(long, value_enum, default_value = "human", global = true)]
+    pub output: OutputFormat,
+
+    /// When to use colors (NO_COLOR and CLICOLOR_FORCE are honored in auto mode)
+    #[arg(long, value_enum, value_name = "WHEN", default_value = "auto", global = true)]
+    pub color: ColorChoice,
+
+    /// Only print errors and warnings
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Trace spawned git commands and git2 operations (-v debug, -vv trace); also set by WT_LOG
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
+    pub verbose: u8,
+
+    /// Show what mutating commands would do without changing anything
+    #[arg(long, global = true)]
+    pub dry_run: bool,
+
+    /// Append a debug trace of this run to <FILE>
+    #[arg(long, value_name = "FILE", global = true)]
+    pub log_file: Option<PathBuf>,
+}
+
 #[derive(Subcommand)]
 pub enum Commands {
     /// Create a new worktree
@@ -21,4 +62,35 @@ pub enum Commands {
     Remove(RemoveCommand),
     /// Open an existing worktree in VSCode
     Open(OpenCommand),
+    /// Show all worktrees with their checkout and working tree state
+    #[command(alias = "list")]
+    Status(StatusCommand),
+    /// Clone a repository, optionally as a bare hub for worktrees
+    Clone(CloneCommand),
+    /// Undo the most recent add or remove
+    Undo(UndoCommand),
+    /// Restore a removed worktree from the operation journal
+    Restore(RestoreCommand),
+    /// List snapshots of uncommitted changes saved by 'wt remove --force'
+    Snapshots(SnapshotsCommand),
+    /// Pack a worktree's files into a tar.gz or zip archive, optionally removing it
+    Archive(ArchiveCommand),
+    /// Recreate a worktree from an archive made by 'wt archive'
+    Unarchive(UnarchiveCommand),
+    /// Create a worktree on a new branch stacked on another worktree's branch
+    Stack(StackCommand),
+    /// Rebase each stacked branch onto its updated parent, parents first
+    Restack(RestackCommand),
+    /// Change or list the directories checked out in a sparse worktree
+    Sparse(SparseCommand),
+    /// Show the per-worktree variables from [env], allocating them if needed
+    Env(EnvCommand),
+    /// Turn a separate clone or plain directory into a managed worktree
+    Adopt(AdoptCommand),
+    /// Detect and fix missing, moved, orphaned or stale worktrees and unused branches
+    Doctor(DoctorCommand),
+    /// Generate a shell completion script
+    Completions(CompletionsCommand),
+    #[command(name = "__complete", hide = true)]
+    Complete(CompleteCommand),
 }
\ No newline at end of file
@@ -1,56 +1,294 @@
 use clap::Args;
-use anyhow::Result;
+use anyhow::{Result, anyhow};
+use is_terminal::IsTerminal;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use crate::config::DEFAULT_NAMING_TEMPLATE;
+use crate::commands::sparse::resolve_directories;
+use crate::error::WtError;
+use crate::git::context::RepoContext;
 use crate::git::repository::Repository;
-use crate::git::branches::BranchManager;
-use crate::git::worktree::{WorktreeManager, BranchMode};
-use crate::ui::prompts::Prompts;
+use crate::git::branches::{Branch, BranchManager};
+use crate::git::environment::EnvManager;
+use crate::git::journal::{record_operation, Operation};
+use crate::git::setup::prepare_worktree;
+use crate::git::worktree::{BranchMode, WorktreeInfo};
+use crate::commands::open::open_worktree;
+use crate::ui::colors::Theme;
+use crate::ui::prompts::{Prompts, CheckedOutAction};
 use crate::ui::display::Display;
-use crate::utils::paths::generate_worktree_path;
-use crate::utils::system::open_vscode;
+use crate::utils::dry_run;
+use crate::utils::logger;
+use crate::utils::paths::{expand_path_template, generate_worktree_path, is_path_taken, normalize_path, suggest_free_path};
+use crate::utils::system::run_post_add_hooks;
+use crate::utils::naming::{build_branch_name, check_branch_name};
+use crate::utils::validation::{validate_path_name, validate_template_path};
 
 #[derive(Debug, Args)]
-pub struct AddCommand {}
+pub struct AddCommand {
+    /// Branch to create the worktree for (local or remote), or the summary with --ticket/--type; prompts when omitted
+    pub branch: Option<String>,
+
+    /// Create a new branch with this name based on BRANCH instead of checking BRANCH out
+    #[arg(short = 'b', long = "new-branch", value_name = "NAME", requires = "branch")]
+    pub new_branch: Option<String>,
+
+    /// Check out any tag, commit SHA or ref with a detached HEAD
+    #[arg(long, value_name = "REF", conflicts_with_all = ["branch", "new_branch"])]
+    pub detach: Option<String>,
+
+    /// Create the worktree at this path instead of the one from path_template
+    #[arg(long, value_name = "DIR")]
+    pub path: Option<PathBuf>,
+
+    /// Only check out this directory (cone-mode sparse checkout); repeat for more, e.g. --sparse web --sparse api
+    #[arg(long, value_name = "DIR")]
+    pub sparse: Vec<String>,
+
+    /// Only check out the directories of this [sparse.profiles] entry; can be repeated
+    #[arg(long, value_name = "NAME")]
+    pub sparse_profile: Vec<String>,
+
+    /// Ticket ID for a new branch named by the [naming] template, with BRANCH as the summary
+    #[arg(long, conflicts_with_all = ["new_branch", "detach"])]
+    pub ticket: Option<String>,
+
+    /// Change type for a new branch named by the [naming] template, e.g. feat or fix
+    #[arg(long = "type", value_name = "TYPE", conflicts_with_all = ["new_branch", "detach"])]
+    pub kind: Option<String>,
+}
 
 impl AddCommand {
     pub fn execute(&self) -> Result<()> {
         Display::show_info("Creating a new worktree...");
-        
-        let repo = Repository::open_current()?;
-        let project_name = repo.get_project_name()?;
-        let branch_manager = BranchManager::new(&repo);
-        
-        // 選擇分支模式
-        let branch_mode = Prompts::select_branch_mode()?;
-        
-        let (branch_name, base_branch) = match branch_mode {
-            BranchMode::NewBranch => {
-                let branch_name = Prompts::input_branch_name()?;
-                let branches = branch_manager.list_local_branches()?;
-                let base_branch = Prompts::select_base_branch(branches)?;
-                (branch_name, Some(base_branch))
+
+        let ctx = RepoContext::discover()?;
+        let branch_manager = BranchManager::new(&ctx.repo);
+        let worktree_manager = ctx.worktree_manager();
+        let worktrees = worktree_manager.list_worktrees()?;
+
+        let (branch_mode, branch_name, base_branch) = match (&self.branch, &self.detach) {
+            // 依命名模板由 ticket、類型與摘要組出新分支名稱，基於預設分支建立
+            _ if self.ticket.is_some() || self.kind.is_some() => {
+                let naming = &ctx.config.naming;
+                let template = naming.template.as_deref().unwrap_or(DEFAULT_NAMING_TEMPLATE);
+                let branch_name = build_branch_name(naming, template,
+                    self.kind.as_deref(), self.ticket.as_deref(), self.branch.as_deref())?;
+                if branch_manager.local_branch_tip(&branch_name).is_some() {
+                    return Err(anyhow!("Branch '{}' already exists", branch_name));
+                }
+                (BranchMode::NewBranch, branch_name, branch_manager.default_branch())
             }
-            BranchMode::ExistingBranch => {
-                let branches = branch_manager.list_all_branches()?;
-                let selected_branch = Prompts::select_existing_branch(branches)?;
-                (selected_branch.name, None)
+            (_, Some(reference)) => {
+                resolve_ref(&ctx.repo, reference)?;
+                (BranchMode::Detached, reference.clone(), None)
+            }
+            (Some(branch), None) => {
+                let mut branches = branch_manager.list_all_branches()?;
+                branch_manager.mark_worktrees(&mut branches, &worktrees);
+                let selected_branch = branches.into_iter()
+                    .find(|b| &b.name == branch)
+                    .ok_or_else(|| anyhow!("Branch '{}' not found", branch))?;
+
+                match &self.new_branch {
+                    Some(new_branch) => {
+                        check_branch_name(new_branch, &ctx.config.naming)?;
+                        (BranchMode::NewBranch, new_branch.clone(), Some(selected_branch.name))
+                    }
+                    None => match resolve_checked_out(&ctx, &selected_branch, false)? {
+                        Resolution::Create(mode, name, base) => (mode, name, base),
+                        _ => return Ok(()),
+                    },
+                }
+            }
+            (None, None) => {
+                // 選擇分支模式
+                match Prompts::select_branch_mode()? {
+                    BranchMode::NewBranch => {
+                        let branch_name = Prompts::input_branch_name(&ctx.config.naming)?;
+                        let mut branches = branch_manager.list_local_branches()?;
+                        branch_manager.mark_worktrees(&mut branches, &worktrees);
+                        branch_manager.sort_for_picker(&mut branches);
+                        let base_branch = Prompts::select_base_branch(branches, &ctx.config.picker)?;
+                        (BranchMode::NewBranch, branch_name, Some(base_branch.name))
+                    }
+                    BranchMode::ExistingBranch => {
+                        let mut branches = branch_manager.list_all_branches()?;
+                        branch_manager.mark_worktrees(&mut branches, &worktrees);
+                        branch_manager.sort_for_picker(&mut branches);
+
+                        // 已被其他 worktree 使用的分支，讓使用者改為開啟或基於它建立新分支
+                        loop {
+                            let selected_branch = Prompts::select_existing_branch(branches.clone(), &ctx.config.picker)?;
+                            match resolve_checked_out(&ctx, &selected_branch, true)? {
+                                Resolution::Create(mode, name, base) => break (mode, name, base),
+                                Resolution::Opened => return Ok(()),
+                                Resolution::PickAnother => continue,
+                            }
+                        }
+                    }
+                    BranchMode::Detached => loop {
+                        let reference = Prompts::input_ref()?;
+                        match resolve_ref(&ctx.repo, &reference) {
+                            Ok(()) => break (BranchMode::Detached, reference, None),
+                            Err(e) => Display::show_error(&e.to_string()),
+                        }
+                    },
+                }
             }
         };
-        
+
         // 生成 worktree 路徑
-        let worktree_path = generate_worktree_path(&project_name, &branch_name)?;
-        
-        // 創建 worktree
-        let worktree_manager = WorktreeManager::new(repo.inner.path().parent().unwrap().to_path_buf());
-        worktree_manager.add_worktree(&worktree_path, &branch_name, &branch_mode, base_branch.as_ref().map(|b| b.name.as_str()))?;
-        
+        let Some(worktree_path) = choose_worktree_path(&ctx, &branch_name, self.path.as_deref(), &worktrees)? else {
+            Display::show_info("Operation cancelled.");
+            Display::show_result("add", &json!({ "cancelled": true }));
+            return Ok(());
+        };
+
+        // 遠端分支會由 git 自動建立對應的本地分支，同樣視為新建立
+        let created_branch = branch_mode != BranchMode::Detached
+            && branch_manager.local_branch_tip(&branch_name).is_none();
+
+        // 創建 worktree；指定了目錄時只取出那些目錄
+        let sparse = resolve_directories(&ctx.config.sparse, &self.sparse_profile, &self.sparse)?;
+        if sparse.is_empty() {
+            worktree_manager.add_worktree(&worktree_path, &branch_name, &branch_mode, base_branch.as_deref())?;
+        } else {
+            worktree_manager.add_worktree_sparse(&worktree_path, &branch_name, &branch_mode, base_branch.as_deref(), &sparse)?;
+        }
+
         Display::show_success(&format!("Worktree created at: {}", worktree_path.display()));
-        
-        // 詢問是否在 VSCode 中開啟
-        if Prompts::confirm_vscode_open(&worktree_path.to_string_lossy())? {
-            open_vscode(&worktree_path)?;
-            Display::show_success("VSCode opened successfully!");
+
+        if !dry_run::is_enabled() {
+            let worktrees = worktree_manager.list_worktrees()?;
+            let created = worktree_manager.find_worktree(&worktrees, &worktree_path.to_string_lossy())?;
+            record_operation(&ctx.repo, Operation::Add, &created, created_branch, None);
+        }
+
+        let target = match branch_mode {
+            BranchMode::NewBranch => base_branch.as_deref().unwrap_or("HEAD"),
+            _ => &branch_name,
+        };
+        let setup = prepare_worktree(&ctx.repo, &ctx.config.setup, &worktree_path, target);
+        let vars = EnvManager::new(&ctx.repo, &ctx.config.env, &ctx.project_name).ensure(&worktree_path, &branch_name)?;
+        run_post_add_hooks(&ctx.config.hooks.post_add, &worktree_path, &branch_name, &vars)?;
+
+        // 詢問是否以設定的方式開啟（非互動或 JSON 模式時略過）
+        let opener = ctx.config.open.opener;
+        if Display::is_human() && !dry_run::is_enabled() && std::io::stdin().is_terminal()
+            && Prompts::confirm_open(&worktree_path.to_string_lossy(), opener)? {
+            // detached worktree 沒有分支，與 Worktree::name 一樣改用目錄名稱
+            let name = match branch_mode {
+                BranchMode::Detached => worktree_path.file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+                _ => branch_name.clone(),
+            };
+            open_worktree(&ctx, opener, &worktree_path, &name, &branch_name, json!({}))?;
         }
-        
+
+        Display::show_result("add", &json!({
+            "path": worktree_path,
+            "branch": branch_name,
+            "base": base_branch,
+            "mode": branch_mode,
+            "env": vars,
+            "sparse": sparse,
+            "setup": setup,
+        }));
+
         Ok(())
     }
+}
+
+// 決定新 worktree 的路徑：避開已存在的目錄與已登記的 worktree（不同分支可能清理成同一個目錄名稱），並在建立前檢查路徑
+pub(crate) fn choose_worktree_path(ctx: &RepoContext, branch: &str, explicit: Option<&Path>, worktrees: &[WorktreeInfo]) -> Result<Option<PathBuf>> {
+    let registered: Vec<PathBuf> = worktrees.iter().map(|wt| wt.path.clone()).collect();
+
+    let path = match explicit {
+        Some(path) => {
+            let path = normalize_path(&std::env::current_dir()?.join(path));
+            validate_path_name(&path)?;
+            if is_path_taken(&path, &registered) {
+                return Err(anyhow!("Cannot create a worktree at {}: it is already in use", path.display()));
+            }
+            path
+        }
+        None => {
+            validate_template_path(&expand_path_template(ctx.path_template(), &ctx.project_name, branch))?;
+            let path = generate_worktree_path(&ctx.root, ctx.path_template(), &ctx.project_name, branch);
+            if !is_path_taken(&path, &registered) {
+                path
+            } else {
+                let suggestion = suggest_free_path(&path, &registered);
+                if std::io::stdin().is_terminal() {
+                    match Prompts::resolve_path_collision(&path, &suggestion, &registered)? {
+                        Some(path) => path,
+                        None => return Ok(None),
+                    }
+                } else {
+                    let owner = worktrees.iter()
+                        .find(|wt| normalize_path(&wt.path) == path)
+                        .map(|wt| format!(" by worktree '{}'", wt.display_name()))
+                        .unwrap_or_default();
+                    Display::show_warning(&format!("{} is already in use{}; using {} instead",
+                        path.display(), owner, suggestion.display()));
+                    suggestion
+                }
+            }
+        }
+    };
+
+    Ok(Some(path))
+}
+
+enum Resolution {
+    Create(BranchMode, String, Option<String>),
+    Opened,
+    PickAnother,
+}
+
+// 處理已被其他 worktree 使用的分支
+fn resolve_checked_out(ctx: &RepoContext, branch: &Branch, allow_pick_another: bool) -> Result<Resolution> {
+    let worktree_path = match &branch.worktree {
+        Some(path) => path,
+        None => return Ok(Resolution::Create(BranchMode::ExistingBranch, branch.name.clone(), None)),
+    };
+
+    if !std::io::stdin().is_terminal() {
+        let theme = Theme::current();
+        return Err(WtError::BranchInUse(format!(
+            "Branch '{}' is already checked out at {}\n\n\
+            {} Solutions:\n\
+            {} Run 'wt add {} -b <new-branch>' to create a new branch based on '{}'\n\
+            {} Run 'wt open {}' to open the existing worktree",
+            branch.name, worktree_path.display(), theme.hint(),
+            theme.bullet(), branch.name, branch.name,
+            theme.bullet(), branch.name
+        )).into());
+    }
+
+    match Prompts::select_checked_out_action(&branch.name, worktree_path, allow_pick_another)? {
+        CheckedOutAction::OpenExisting => {
+            let result = open_worktree(ctx, ctx.config.open.opener, worktree_path, &branch.name, &branch.name,
+                json!({
+                    "opened": worktree_path,
+                    "branch": branch.name,
+                }))?;
+            Display::show_result("add", &result);
+            Ok(Resolution::Opened)
+        }
+        CheckedOutAction::BranchOff => {
+            let new_branch = Prompts::input_branch_name(&ctx.config.naming)?;
+            Ok(Resolution::Create(BranchMode::NewBranch, new_branch, Some(branch.name.clone())))
+        }
+        CheckedOutAction::PickAnother => Ok(Resolution::PickAnother),
+    }
+}
+
+fn resolve_ref(repo: &Repository, reference: &str) -> Result<()> {
+    logger::git2(&format!("revparse {}", reference), || repo.inner.revparse_single(reference))
+        .and_then(|object| object.peel_to_commit())
+        .map(|_| ())
+        .map_err(|_| anyhow!("'{}' does not resolve to a commit", reference))
 }
\ No newline at end of file
@@ -0,0 +1,210 @@
+use clap::Args;
+use anyhow::{Result, anyhow};
+use serde_json::json;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::git::branches::BranchManager;
+use crate::git::context::RepoContext;
+use crate::git::journal::{record_move, record_operation, Operation};
+use crate::git::repository::Repository;
+use crate::git::worktree::BranchMode;
+use crate::ui::display::Display;
+use crate::utils::dry_run::{self, PlannedAction};
+use crate::utils::naming::check_branch_name;
+use crate::utils::paths::generate_worktree_path;
+use crate::utils::validation::validate_branch_name;
+
+// 取得 clone 的 HEAD 時暫存使用的 ref
+const ADOPT_REF: &str = "refs/wt/adopt";
+
+#[derive(Debug, Args)]
+pub struct AdoptCommand {
+    /// Separate clone or plain directory to turn into a worktree
+    pub dir: PathBuf,
+
+    /// Branch for the worktree (defaults to the branch checked out in the clone)
+    #[arg(short, long)]
+    pub branch: Option<String>,
+}
+
+impl AdoptCommand {
+    pub fn execute(&self) -> Result<()> {
+        let ctx = RepoContext::discover()?;
+        let source = env::current_dir()?.join(&self.dir);
+        let source = source.canonicalize()
+            .map_err(|e| anyhow!("Cannot adopt '{}': {}", self.dir.display(), e))?;
+
+        if !source.is_dir() {
+            return Err(anyhow!("'{}' is not a directory", source.display()));
+        }
+        if source.join(".git").is_file() {
+            return Err(anyhow!("'{}' is already a linked worktree; use 'wt doctor' to repair worktrees", source.display()));
+        }
+        if source == ctx.root.canonicalize().unwrap_or_else(|_| ctx.root.clone()) {
+            return Err(anyhow!("Cannot adopt the repository itself"));
+        }
+
+        // 另一份完整 clone 時取得它的分支與 HEAD；一般目錄則沒有
+        let clone = if source.join(".git").is_dir() { Some(Repository::open(&source)?) } else { None };
+        let clone_branch = clone.as_ref().and_then(|clone| {
+            let head = clone.inner.head().ok()?;
+            head.is_branch().then(|| head.shorthand().map(|name| name.to_string())).flatten()
+        });
+
+        let branch = self.branch.clone().or(clone_branch)
+            .ok_or_else(|| anyhow!("Cannot determine the branch for '{}'; pass --branch <name>", source.display()))?;
+        validate_branch_name(&branch)?;
+
+        let branch_manager = BranchManager::new(&ctx.repo);
+        let (branch_mode, base) = match &clone {
+            Some(clone) => resolve_clone_branch(&ctx, clone, &source, &branch)?,
+            None => match branch_manager.local_branch_tip(&branch) {
+                Some(_) => (BranchMode::ExistingBranch, None),
+                None => (BranchMode::NewBranch, branch_manager.default_branch()),
+            },
+        };
+        // 在此建立新分支時套用命名規則
+        if branch_mode == BranchMode::NewBranch {
+            check_branch_name(&branch, &ctx.config.naming)?;
+        }
+
+        let target = generate_worktree_path(&ctx.root, ctx.path_template(), &ctx.project_name, &branch);
+        // 目錄已在模板指定的位置時，先移到一旁再建立 worktree
+        let moved_aside = target.exists() && target.canonicalize().ok().as_deref() == Some(source.as_path());
+        if target.exists() && !moved_aside {
+            return Err(anyhow!("Cannot adopt into {}: it already exists", target.display()));
+        }
+
+        let staging = if moved_aside {
+            let staging = source.with_extension("wt-adopt");
+            if !dry_run::is_enabled() {
+                fs::rename(&source, &staging)?;
+            }
+            staging
+        } else {
+            source.clone()
+        };
+
+        let worktree_manager = ctx.worktree_manager();
+        if let Err(e) = worktree_manager.add_worktree_from_directory(&staging, &target, &branch, &branch_mode, base.as_deref()) {
+            if moved_aside && !target.exists() {
+                fs::rename(&staging, &source)?;
+            }
+            return Err(e);
+        }
+        Display::show_success(&format!("Adopted {} as worktree '{}' at: {}", source.display(), branch, target.display()));
+
+        let retired = retire(&ctx, clone.as_ref(), &staging)?;
+        if let Some(retired) = retired.as_ref().filter(|_| !dry_run::is_enabled()) {
+            Display::show_warning(&format!(
+                "The old clone has branches or stashes that are not in this repository; it was kept at {}",
+                retired.display()));
+        }
+
+        if !dry_run::is_enabled() {
+            let worktrees = worktree_manager.list_worktrees()?;
+            let created = worktree_manager.find_worktree(&worktrees, &target.to_string_lossy())?;
+            // 目錄換了位置時記錄為移動，復原時移回原處；原地轉換則記錄為新增
+            if moved_aside {
+                record_operation(&ctx.repo, Operation::Add, &created, branch_mode == BranchMode::NewBranch, None);
+            } else {
+                record_move(&ctx.repo, &source, &created);
+            }
+        }
+
+        Display::show_result("adopt", &json!({
+            "source": source,
+            "path": target,
+            "branch": branch,
+            "mode": branch_mode,
+            "retired_clone": retired,
+        }));
+
+        Ok(())
+    }
+}
+
+// 確認 clone 與倉庫有共同的歷史，並決定如何建立分支
+fn resolve_clone_branch(ctx: &RepoContext, clone: &Repository, source: &Path, branch: &str) -> Result<(BranchMode, Option<String>)> {
+    let head = clone.inner.head()
+        .and_then(|head| head.peel_to_commit())
+        .map_err(|_| anyhow!("'{}' has no commits to adopt", source.display()))?
+        .id();
+
+    let commit = if ctx.repo.inner.find_commit(head).is_ok() {
+        head
+    } else if dry_run::is_enabled() {
+        dry_run::record(PlannedAction::Fetch { from: source.to_path_buf(), reference: "HEAD".to_string() });
+        return Ok((BranchMode::NewBranch, Some(head.to_string())));
+    } else {
+        let fetched = ctx.repo.fetch_ref(source, "HEAD", ADOPT_REF);
+        if let Ok(mut reference) = ctx.repo.inner.find_reference(ADOPT_REF) {
+            reference.delete().ok();
+        }
+        fetched?
+    };
+
+    let branch_manager = BranchManager::new(&ctx.repo);
+    let ours = branch_manager.default_branch()
+        .and_then(|name| branch_manager.local_branch_tip(&name))
+        .and_then(|tip| git2::Oid::from_str(&tip).ok())
+        .or_else(|| ctx.repo.inner.head().ok().and_then(|head| head.target()));
+    let shares_history = ours.is_some_and(|ours| ctx.repo.inner.merge_base(ours, commit).is_ok());
+    if !shares_history {
+        return Err(anyhow!("'{}' does not share history with this repository", source.display()));
+    }
+
+    let commit = commit.to_string();
+    match branch_manager.local_branch_tip(branch) {
+        None => Ok((BranchMode::NewBranch, Some(commit))),
+        Some(tip) if tip == commit => Ok((BranchMode::ExistingBranch, None)),
+        // clone 中有新的 commit：快轉本地分支
+        Some(tip) if is_descendant(ctx, &commit, &tip) => {
+            branch_manager.set_branch_tip(branch, &commit)?;
+            Ok((BranchMode::ExistingBranch, None))
+        }
+        Some(_) => Err(anyhow!(
+            "Branch '{}' in this repository has diverged from the one in '{}'; pass --branch <new-name> to adopt it under another name",
+            branch, source.display())),
+    }
+}
+
+// 移除舊的 clone；只有當它的分支與 stash 都已存在於倉庫中時才刪除，否則改名保留
+fn retire(ctx: &RepoContext, clone: Option<&Repository>, dir: &Path) -> Result<Option<PathBuf>> {
+    let safe_to_delete = match clone {
+        None => true,
+        Some(clone) => {
+            let has_stash = clone.inner.find_reference("refs/stash").is_ok();
+            let all_branches_known = clone.inner.branches(Some(git2::BranchType::Local))?
+                .flatten()
+                .filter_map(|(branch, _)| branch.get().target())
+                .all(|oid| ctx.repo.inner.find_commit(oid).is_ok());
+            !has_stash && all_branches_known
+        }
+    };
+
+    if safe_to_delete {
+        if dry_run::is_enabled() {
+            dry_run::record(PlannedAction::DeletePath { path: dir.to_path_buf() });
+        } else {
+            fs::remove_dir_all(dir)?;
+        }
+        return Ok(None);
+    }
+
+    let retired = dir.with_extension("wt-retired");
+    if dry_run::is_enabled() {
+        dry_run::record(PlannedAction::MoveFiles { from: dir.to_path_buf(), to: retired.clone() });
+    } else {
+        fs::rename(dir, &retired)?;
+    }
+    Ok(Some(retired))
+}
+
+fn is_descendant(ctx: &RepoContext, commit: &str, ancestor: &str) -> bool {
+    let (Ok(commit), Ok(ancestor)) = (git2::Oid::from_str(commit), git2::Oid::from_str(ancestor)) else {
+        return false;
+    };
+    ctx.repo.inner.graph_descendant_of(commit, ancestor).unwrap_or(false)
+}
\ No newline at end of file
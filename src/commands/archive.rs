@@ -0,0 +1,182 @@
+use clap::Args;
+use anyhow::{Result, anyhow};
+use serde_json::json;
+use std::env;
+use std::path::PathBuf;
+use crate::git::branches::BranchManager;
+use crate::git::context::RepoContext;
+use crate::git::journal::{record_operation, Operation};
+use crate::git::worktree::BranchMode;
+use crate::ui::display::Display;
+use crate::ui::prompts::Prompts;
+use crate::utils::archive::{self, ArchiveFormat, ArchiveManifest};
+use crate::utils::dry_run::{self, PlannedAction};
+use crate::utils::paths::clean_branch_name;
+use crate::utils::time::{format_utc_timestamp, now_timestamp};
+
+#[derive(Debug, Args)]
+pub struct ArchiveCommand {
+    /// Worktree to archive (branch name, directory name or path); prompts when omitted
+    pub worktree: Option<String>,
+
+    /// Also pack files ignored by .gitignore, such as build outputs
+    #[arg(long)]
+    pub include_ignored: bool,
+
+    /// Archive format
+    #[arg(long, value_enum, default_value_t = ArchiveFormat::TarGz)]
+    pub format: ArchiveFormat,
+
+    /// Where to write the archive (defaults to <project>-<branch>-<date> in the current directory)
+    #[arg(long, value_name = "FILE")]
+    pub file: Option<PathBuf>,
+
+    /// Remove the worktree after archiving it
+    #[arg(long)]
+    pub remove: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct UnarchiveCommand {
+    /// Archive created by 'wt archive'
+    pub archive: PathBuf,
+
+    /// Where to recreate the worktree (defaults to its original path)
+    #[arg(long)]
+    pub path: Option<PathBuf>,
+}
+
+impl ArchiveCommand {
+    pub fn execute(&self) -> Result<()> {
+        let ctx = RepoContext::discover()?;
+        let worktree_manager = ctx.worktree_manager();
+        let worktrees = worktree_manager.list_worktrees()?;
+
+        let worktree = match &self.worktree {
+            Some(query) => worktree_manager.find_worktree(&worktrees, query)?,
+            None => Prompts::select_worktree(worktrees, &ctx.config.picker)?,
+        };
+
+        // 記錄與預設分支的分岔點，方便日後了解封存的內容
+        let branch_manager = BranchManager::new(&ctx.repo);
+        let base = branch_manager.default_branch();
+        let base_commit = base.as_deref().and_then(|base| {
+            let base_oid = ctx.repo.inner.revparse_single(base).ok()?.peel_to_commit().ok()?.id();
+            let commit_oid = git2::Oid::from_str(&worktree.commit).ok()?;
+            ctx.repo.inner.merge_base(base_oid, commit_oid).ok().map(|oid| oid.to_string())
+        });
+
+        let timestamp = now_timestamp();
+        let manifest = ArchiveManifest {
+            branch: (!worktree.is_detached).then(|| worktree.branch.clone()),
+            commit: worktree.commit.clone(),
+            base,
+            base_commit,
+            date: format_utc_timestamp(timestamp),
+            timestamp,
+            path: worktree.path.clone(),
+            include_ignored: self.include_ignored,
+        };
+
+        let destination = match &self.file {
+            Some(file) => env::current_dir()?.join(file),
+            None => {
+                let date = manifest.date.replace(['-', ':'], "");
+                env::current_dir()?.join(format!("{}-{}-{}.{}",
+                    ctx.project_name, clean_branch_name(&worktree.name()), date, self.format.extension()))
+            }
+        };
+        if destination.exists() {
+            return Err(anyhow!("Destination '{}' already exists", destination.display()));
+        }
+
+        let files = worktree_manager.list_files(&worktree.path, self.include_ignored)?;
+
+        if dry_run::is_enabled() {
+            dry_run::record(PlannedAction::WriteFile { path: destination.clone() });
+        } else {
+            Display::show_info(&format!("Archiving {} files from {}...", files.len(), worktree.path.display()));
+            archive::create(self.format, &worktree.path, &files, &manifest, &destination)?;
+        }
+        Display::show_success(&format!("Worktree '{}' archived to: {}", worktree.display_name(), destination.display()));
+
+        // 封存檔已包含未提交的變更，因此可以強制移除
+        if self.remove {
+            worktree_manager.remove_worktree(&worktree.path, true)?;
+            record_operation(&ctx.repo, Operation::Remove, &worktree, false, None);
+            Display::show_success(&format!("Worktree '{}' removed", worktree.display_name()));
+        }
+
+        Display::show_result("archive", &json!({
+            "archive": destination,
+            "format": self.format,
+            "files": files.len(),
+            "manifest": manifest,
+            "removed": self.remove,
+        }));
+
+        Ok(())
+    }
+}
+
+impl UnarchiveCommand {
+    pub fn execute(&self) -> Result<()> {
+        let ctx = RepoContext::discover()?;
+        let archive_path = env::current_dir()?.join(&self.archive);
+        let manifest = archive::read_manifest(&archive_path)?;
+
+        let path = match &self.path {
+            Some(path) => env::current_dir()?.join(path),
+            None => manifest.path.clone(),
+        };
+        if path.exists() {
+            return Err(anyhow!("Cannot unarchive to {}: it already exists (use --path to choose another location)",
+                path.display()));
+        }
+
+        let commit_oid = git2::Oid::from_str(&manifest.commit).ok()
+            .filter(|oid| ctx.repo.inner.find_commit(*oid).is_ok())
+            .ok_or_else(|| anyhow!("Commit {} from the archive is not in this repository", manifest.commit))?;
+
+        // 分支仍存在時直接使用，已被刪除時由封存時的 commit 重新建立
+        let branch_manager = BranchManager::new(&ctx.repo);
+        let (branch_mode, reference, base) = match &manifest.branch {
+            Some(branch) => match branch_manager.local_branch_tip(branch) {
+                Some(tip) => {
+                    if tip != commit_oid.to_string() {
+                        Display::show_warning(&format!(
+                            "Branch '{}' has moved since it was archived; the archived files are restored on top of its current tip",
+                            branch));
+                    }
+                    (BranchMode::ExistingBranch, branch.clone(), None)
+                }
+                None => (BranchMode::NewBranch, branch.clone(), Some(manifest.commit.clone())),
+            },
+            None => (BranchMode::Detached, manifest.commit.clone(), None),
+        };
+
+        let worktree_manager = ctx.worktree_manager();
+        worktree_manager.add_worktree_without_checkout(&path, &reference, &branch_mode, base.as_deref())?;
+
+        if dry_run::is_enabled() {
+            dry_run::record(PlannedAction::ExtractArchive { archive: archive_path.clone(), path: path.clone() });
+        } else {
+            archive::extract(&archive_path, &path)?;
+            worktree_manager.reset_index(&path)?;
+
+            let worktrees = worktree_manager.list_worktrees()?;
+            let created = worktree_manager.find_worktree(&worktrees, &path.to_string_lossy())?;
+            record_operation(&ctx.repo, Operation::Add, &created, branch_mode == BranchMode::NewBranch, None);
+        }
+
+        Display::show_success(&format!("Worktree restored at: {}", path.display()));
+        Display::show_result("unarchive", &json!({
+            "path": path,
+            "branch": manifest.branch,
+            "commit": manifest.commit,
+            "mode": branch_mode,
+        }));
+
+        Ok(())
+    }
+}
\ No newline at end of file
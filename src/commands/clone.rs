@@ -0,0 +1,168 @@
+use clap::Args;
+use anyhow::{Result, anyhow};
+use is_terminal::IsTerminal;
+use serde_json::json;
+use std::env;
+use std::path::{Path, PathBuf};
+use crate::config::{Config, CONFIG_FILE_NAME};
+use crate::git::repository::{Repository, extract_project_name_from_url, HUB_GIT_DIR};
+use crate::git::branches::BranchManager;
+use crate::git::environment::{EnvManager, EnvVars};
+use crate::git::setup::{pending_steps, prepare_worktree, SetupReport};
+use crate::git::worktree::{WorktreeManager, BranchMode};
+use crate::ui::display::Display;
+use crate::ui::prompts::Prompts;
+use crate::utils::dry_run::{self, PlannedAction};
+use crate::utils::paths::generate_worktree_path;
+use crate::utils::system::run_post_add_hooks;
+
+#[derive(Debug, Args)]
+pub struct CloneCommand {
+    /// URL of the repository to clone
+    pub url: String,
+
+    /// Directory to clone into (defaults to the project name)
+    pub dir: Option<PathBuf>,
+
+    /// Clone as a bare "hub" that holds all worktrees as siblings
+    #[arg(long)]
+    pub bare_hub: bool,
+
+    /// Run the post-add hooks, [env] and [setup] steps from the cloned repository's own .wt.toml without asking
+    #[arg(long)]
+    pub run_hooks: bool,
+}
+
+impl CloneCommand {
+    pub fn execute(&self) -> Result<()> {
+        let project_name = extract_project_name_from_url(&self.url)
+            .ok_or_else(|| anyhow!("Cannot determine project name from '{}'", self.url))?;
+
+        let target = match &self.dir {
+            Some(dir) => dir.clone(),
+            None => PathBuf::from(&project_name),
+        };
+        let target = env::current_dir()?.join(target);
+
+        if target.exists() {
+            return Err(anyhow!("Destination '{}' already exists", target.display()));
+        }
+
+        if dry_run::is_enabled() {
+            return self.plan(&project_name, target);
+        }
+
+        Display::show_info(&format!("Cloning {} into {}...", self.url, target.display()));
+
+        if self.bare_hub {
+            Repository::clone_bare_hub(&self.url, &target)?;
+        } else {
+            Repository::clone_standard(&self.url, &target)?;
+        }
+
+        let repo = Repository::open(&target)?;
+        let root = repo.main_root()?;
+
+        // 寫入起始設定檔；一般倉庫中將它排除，避免變成未追蹤檔案
+        let wrote_starter = Config::write_starter(&root)?;
+        if wrote_starter {
+            if !self.bare_hub {
+                repo.exclude_file(CONFIG_FILE_NAME)?;
+            }
+            Display::show_info(&format!("Wrote starter {}", root.join(CONFIG_FILE_NAME).display()));
+        }
+        let config = repo.load_config()?;
+
+        let default_branch = BranchManager::new(&repo).current_branch()
+            .ok_or_else(|| anyhow!("Cannot determine the default branch of '{}'", self.url))?;
+
+        // bare hub 沒有工作目錄，為預設分支建立 worktree；一般倉庫本身即是預設分支的 worktree
+        let default_worktree = if self.bare_hub {
+            let path = generate_worktree_path(&root, config.path_template(true), &project_name, &default_branch);
+            WorktreeManager::new(root.clone())
+                .add_worktree(&path, &default_branch, &BranchMode::ExistingBranch, None)?;
+            path
+        } else {
+            root.clone()
+        };
+
+        // 倉庫自帶的 .wt.toml 來自遠端，執行其中的指令或寫入檔案前要先取得同意
+        let env_manager = EnvManager::new(&repo, &config.env, &project_name);
+        let steps = repo_config_steps(&config, &env_manager, &default_worktree);
+        let trusted = wrote_starter || steps.is_empty() || self.run_hooks
+            || (Display::is_human() && std::io::stdin().is_terminal() && Prompts::confirm_repo_config(&steps)?);
+
+        let (setup, vars) = if trusted {
+            // git clone 不會初始化 submodule，一般倉庫與 bare hub 都在這裡處理
+            let setup = prepare_worktree(&repo, &config.setup, &default_worktree, &default_branch);
+            let vars = env_manager.ensure(&default_worktree, &default_branch)?;
+            run_post_add_hooks(&config.hooks.post_add, &default_worktree, &default_branch, &vars)?;
+            (setup, vars)
+        } else {
+            Display::show_warning(&format!(
+                "Skipped {} step(s) from the repository's {}: {}. Review the file, then run them yourself or clone again with --run-hooks",
+                steps.len(), CONFIG_FILE_NAME, steps.join("; ")));
+            (SetupReport::default(), EnvVars::new())
+        };
+
+        Display::show_success(&format!("Repository ready at: {}", root.display()));
+        Display::show_success(&format!("Default branch '{}' checked out at: {}", default_branch, default_worktree.display()));
+        Display::show_result("clone", &json!({
+            "root": root,
+            "bare_hub": self.bare_hub,
+            "default_branch": default_branch,
+            "default_worktree": default_worktree,
+            "setup": setup,
+            "env": vars,
+            "skipped": if trusted { Vec::new() } else { steps },
+        }));
+
+        Ok(())
+    }
+
+    // 乾跑：倉庫尚未存在，改用 ls-remote 推算預設分支並以預設設定規劃後續步驟
+    fn plan(&self, project_name: &str, target: PathBuf) -> Result<()> {
+        let default_branch = Repository::remote_default_branch(&self.url)?;
+
+        dry_run::record(PlannedAction::Clone {
+            url: self.url.clone(),
+            path: target.clone(),
+            bare_hub: self.bare_hub,
+        });
+        let git_dir = if self.bare_hub { target.join(HUB_GIT_DIR) } else { target.clone() };
+        dry_run::record(PlannedAction::ConfigureFetch { path: git_dir });
+        dry_run::record(PlannedAction::WriteFile { path: target.join(CONFIG_FILE_NAME) });
+
+        let config = Config::default();
+        let default_worktree = if self.bare_hub {
+            let path = generate_worktree_path(&target, config.path_template(true), project_name, &default_branch);
+            WorktreeManager::new(target.clone())
+                .add_worktree(&path, &default_branch, &BranchMode::ExistingBranch, None)?;
+            path
+        } else {
+            target.clone()
+        };
+
+        Display::show_result("clone", &json!({
+            "root": target,
+            "bare_hub": self.bare_hub,
+            "default_branch": default_branch,
+            "default_worktree": default_worktree,
+        }));
+
+        Ok(())
+    }
+}
+
+// 倉庫的 .wt.toml 中會在新 worktree 執行或寫入的項目
+fn repo_config_steps(config: &Config, env_manager: &EnvManager, worktree: &Path) -> Vec<String> {
+    let mut steps: Vec<String> = config.hooks.post_add.iter()
+        .map(|hook| format!("run '{}'", hook))
+        .collect();
+    if env_manager.is_configured() {
+        let names: Vec<&str> = config.env.vars.keys().map(|name| name.as_str()).collect();
+        steps.push(format!("write {} with {}", env_manager.file_path(worktree).display(), names.join(", ")));
+    }
+    steps.extend(pending_steps(&config.setup, worktree));
+    steps
+}
\ No newline at end of file
@@ -0,0 +1,165 @@
+use clap::{Args, CommandFactory, ValueEnum};
+use anyhow::Result;
+use clap_complete::Shell;
+use std::io;
+use crate::cli::Cli;
+use crate::git::branches::BranchManager;
+use crate::git::context::RepoContext;
+
+#[derive(Debug, Args)]
+pub struct CompletionsCommand {
+    /// Shell to generate the completion script for (branch and worktree names are completed in bash, zsh and fish)
+    pub shell: Shell,
+}
+
+// 供補全腳本呼叫，輸出即時的分支或 worktree 名稱（每行一個）
+#[derive(Debug, Args)]
+pub struct CompleteCommand {
+    pub kind: CompleteKind,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum CompleteKind {
+    Branches,
+    Worktrees,
+}
+
+const BASH_DYNAMIC: &str = r#"
+_wt_dynamic() {
+    local cur="${COMP_WORDS[COMP_CWORD]}"
+    local prev="${COMP_WORDS[COMP_CWORD-1]}"
+    local -a repo_args=()
+    local i
+    for ((i = 1; i < COMP_CWORD - 1; i++)); do
+        case "${COMP_WORDS[i]}" in
+            -C|--repo)
+                local opt="${COMP_WORDS[i]}"
+                [[ "${COMP_WORDS[i+1]}" == "=" ]] && ((i++))
+                ((i++))
+                repo_args+=("$opt" "${COMP_WORDS[i]/#\~/$HOME}")
+                ;;
+        esac
+    done
+    if [[ "$cur" != -* ]]; then
+        case "$prev" in
+            open|remove|rm|delete)
+                COMPREPLY=($(compgen -W "$(wt "${repo_args[@]}" __complete worktrees 2>/dev/null)" -- "$cur"))
+                return 0
+                ;;
+            add)
+                COMPREPLY=($(compgen -W "$(wt "${repo_args[@]}" __complete branches 2>/dev/null)" -- "$cur"))
+                return 0
+                ;;
+        esac
+    fi
+    _wt "$@"
+}
+
+complete -F _wt_dynamic -o bashdefault -o default wt
+"#;
+
+const ZSH_DYNAMIC: &str = r#"
+_wt_dynamic() {
+    local -a items repo_args
+    local i
+    for ((i = 2; i < CURRENT - 1; i++)); do
+        case ${words[i]} in
+            -C|--repo)
+                repo_args+=(${words[i]} ${words[i+1]/#\~/$HOME})
+                ((i++))
+                ;;
+            --repo=*)
+                repo_args+=(${words[i]/#--repo=\~/--repo=$HOME})
+                ;;
+        esac
+    done
+    if [[ ${words[CURRENT]} != -* ]]; then
+        case ${words[CURRENT-1]} in
+            open|remove|rm|delete)
+                items=(${(f)"$(wt $repo_args __complete worktrees 2>/dev/null)"})
+                compadd -a items
+                return
+                ;;
+            add)
+                items=(${(f)"$(wt $repo_args __complete branches 2>/dev/null)"})
+                compadd -a items
+                return
+                ;;
+        esac
+    fi
+    _wt "$@"
+}
+
+compdef _wt_dynamic wt
+"#;
+
+const FISH_DYNAMIC: &str = r#"
+function __wt_complete_names
+    set -l tokens (commandline -opc)
+    set -l repo_args
+    set -l i 2
+    while test $i -le (count $tokens)
+        switch $tokens[$i]
+            case -C --repo
+                if test $i -lt (count $tokens)
+                    set -a repo_args $tokens[$i] $tokens[(math $i + 1)]
+                end
+                set i (math $i + 1)
+            case '--repo=*'
+                set -a repo_args $tokens[$i]
+        end
+        set i (math $i + 1)
+    end
+    wt $repo_args __complete $argv 2>/dev/null
+end
+
+complete -c wt -n '__fish_seen_subcommand_from open remove rm delete' -f -a '(__wt_complete_names worktrees)'
+complete -c wt -n '__fish_seen_subcommand_from add' -f -a '(__wt_complete_names branches)'
+"#;
+
+impl CompletionsCommand {
+    pub fn execute(&self) -> Result<()> {
+        let mut command = Cli::command();
+        clap_complete::generate(self.shell, &mut command, "wt", &mut io::stdout());
+
+        // 附加動態補全：分支與 worktree 名稱由 `wt __complete` 即時提供，並沿用命令列上的 -C 與 --repo；
+        // PowerShell 與 Elvish 只有靜態補全
+        match self.shell {
+            Shell::Bash => print!("{}", BASH_DYNAMIC),
+            Shell::Zsh => print!("{}", ZSH_DYNAMIC),
+            Shell::Fish => print!("{}", FISH_DYNAMIC),
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
+
+impl CompleteCommand {
+    pub fn execute(&self) -> Result<()> {
+        // 補全時不顯示錯誤，不在倉庫中就不輸出任何項目
+        let ctx = match RepoContext::discover() {
+            Ok(ctx) => ctx,
+            Err(_) => return Ok(()),
+        };
+
+        let names: Vec<String> = match self.kind {
+            CompleteKind::Branches => BranchManager::new(&ctx.repo)
+                .list_all_branches()?
+                .into_iter()
+                .map(|b| b.name)
+                .collect(),
+            CompleteKind::Worktrees => ctx.worktree_manager()
+                .list_worktrees()?
+                .iter()
+                .map(|wt| wt.name())
+                .collect(),
+        };
+
+        for name in names {
+            println!("{}", name);
+        }
+
+        Ok(())
+    }
+}
\ No newline at end of file
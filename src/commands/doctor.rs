@@ -0,0 +1,355 @@
+use clap::Args;
+use anyhow::Result;
+use is_terminal::IsTerminal;
+use serde::Serialize;
+use serde_json::json;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::git::branches::BranchManager;
+use crate::git::context::RepoContext;
+use crate::git::journal::{record_move, record_operation, Operation};
+use crate::git::worktree::{BranchMode, WorktreeInfo};
+use crate::ui::display::Display;
+use crate::ui::prompts::Prompts;
+use crate::utils::dry_run::{self, PlannedAction};
+use crate::utils::paths::generate_worktree_path;
+
+#[derive(Debug, Args)]
+pub struct DoctorCommand {
+    /// Offer a fix for each issue; without a terminal only non-destructive fixes are applied
+    #[arg(long)]
+    pub fix: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Issue {
+    // 已登記的 worktree 目錄被手動刪除
+    MissingDirectory { path: PathBuf, branch: String },
+    // .git/worktrees/<id> 缺少 gitdir 或 HEAD，無法使用
+    StaleAdminEntry { admin_dir: PathBuf },
+    // worktree 的 .git 檔案指向不存在的位置（例如主倉庫被移動）
+    BrokenLink { path: PathBuf },
+    // worktree 目錄被移動，.git 檔案仍指向這個倉庫
+    MovedWorktree { path: PathBuf },
+    // worktree 根目錄下未登記的 worktree 目錄
+    OrphanedDirectory { path: PathBuf, branch: Option<String> },
+    // 沒有任何 worktree 使用的本地分支
+    UnusedBranch { branch: String, merged: bool },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Fix {
+    Prune,
+    Repair,
+    Reregister,
+    DeleteAdminEntry,
+    DeleteDirectory,
+    DeleteBranch,
+}
+
+impl Fix {
+    fn label(&self) -> &'static str {
+        match self {
+            Fix::Prune => "Prune the worktree entry (git worktree prune)",
+            Fix::Repair => "Repair the links (git worktree repair)",
+            Fix::Reregister => "Re-register the directory as a worktree",
+            Fix::DeleteAdminEntry => "Delete the stale admin entry",
+            Fix::DeleteDirectory => "Delete the directory",
+            Fix::DeleteBranch => "Delete the branch",
+        }
+    }
+
+    // 不會刪除使用者資料的修復，可以在非互動模式下自動套用
+    fn is_safe(&self) -> bool {
+        !matches!(self, Fix::DeleteDirectory | Fix::DeleteBranch)
+    }
+}
+
+impl Issue {
+    fn describe(&self) -> String {
+        match self {
+            Issue::MissingDirectory { path, branch } => {
+                format!("Worktree '{}' is registered but {} is missing", branch, path.display())
+            }
+            Issue::StaleAdminEntry { admin_dir } => {
+                format!("Stale worktree admin entry {}", admin_dir.display())
+            }
+            Issue::BrokenLink { path } => {
+                format!("Worktree {} does not link back to this repository (was the repository moved?)", path.display())
+            }
+            Issue::MovedWorktree { path } => {
+                format!("Worktree {} was moved and is no longer registered at this path", path.display())
+            }
+            Issue::OrphanedDirectory { path, branch: Some(branch) } => {
+                format!("Directory {} looks like a worktree for '{}' but is not registered", path.display(), branch)
+            }
+            Issue::OrphanedDirectory { path, branch: None } => {
+                format!("Directory {} looks like a worktree but is not registered", path.display())
+            }
+            Issue::UnusedBranch { branch, merged: true } => {
+                format!("Branch '{}' is not checked out in any worktree and is merged into the default branch", branch)
+            }
+            Issue::UnusedBranch { branch, merged: false } => {
+                format!("Branch '{}' is not checked out in any worktree", branch)
+            }
+        }
+    }
+
+    fn fixes(&self) -> Vec<Fix> {
+        match self {
+            Issue::MissingDirectory { .. } => vec![Fix::Prune],
+            Issue::StaleAdminEntry { .. } => vec![Fix::DeleteAdminEntry],
+            Issue::BrokenLink { .. } | Issue::MovedWorktree { .. } => vec![Fix::Repair],
+            Issue::OrphanedDirectory { branch: Some(_), .. } => vec![Fix::Reregister, Fix::DeleteDirectory],
+            Issue::OrphanedDirectory { branch: None, .. } => vec![Fix::DeleteDirectory],
+            Issue::UnusedBranch { .. } => vec![Fix::DeleteBranch],
+        }
+    }
+}
+
+impl DoctorCommand {
+    pub fn execute(&self) -> Result<()> {
+        let ctx = RepoContext::discover()?;
+        let issues = diagnose(&ctx)?;
+
+        if issues.is_empty() {
+            Display::show_success("No problems found.");
+            Display::show_result("doctor", &json!({ "issues": [], "fixed": [] }));
+            return Ok(());
+        }
+
+        let interactive = Display::is_human() && std::io::stdin().is_terminal();
+        let mut fixed = Vec::new();
+        let mut pruned = false;
+
+        for issue in &issues {
+            let fixes = issue.fixes();
+            Display::show_warning(&issue.describe());
+
+            if !self.fix {
+                continue;
+            }
+
+            let chosen = if interactive {
+                let labels: Vec<String> = fixes.iter().map(|fix| fix.label().to_string()).collect();
+                Prompts::select_fix(&issue.describe(), &labels)?.map(|index| fixes[index])
+            } else {
+                fixes.first().copied().filter(|fix| fix.is_safe())
+            };
+
+            if let Some(fix) = chosen {
+                // git worktree prune 一次就會清除所有遺失的項目
+                if fix != Fix::Prune || !pruned {
+                    apply_fix(&ctx, issue, fix)?;
+                }
+                pruned |= fix == Fix::Prune;
+                Display::show_success(&format!("{}: done", fix.label()));
+                fixed.push(json!({ "issue": issue, "fix": fix }));
+            }
+        }
+
+        if !self.fix {
+            Display::show_info("Run 'wt doctor --fix' to fix these issues.");
+        }
+
+        let issues: Vec<_> = issues.iter()
+            .map(|issue| json!({ "issue": issue, "message": issue.describe(), "fixes": issue.fixes() }))
+            .collect();
+        Display::show_result("doctor", &json!({ "issues": issues, "fixed": fixed }));
+
+        Ok(())
+    }
+}
+
+fn diagnose(ctx: &RepoContext) -> Result<Vec<Issue>> {
+    let worktree_manager = ctx.worktree_manager();
+    let worktrees = worktree_manager.list_worktrees()?;
+    let admin_root = ctx.repo.inner.commondir().join("worktrees");
+    let mut issues = Vec::new();
+
+    // 管理資料不完整的項目
+    if let Ok(entries) = fs::read_dir(&admin_root) {
+        for entry in entries.flatten().filter(|entry| entry.path().is_dir()) {
+            let admin_dir = entry.path();
+            if !admin_dir.join("gitdir").is_file() || !admin_dir.join("HEAD").is_file() {
+                issues.push(Issue::StaleAdminEntry { admin_dir });
+            }
+        }
+    }
+
+    // linked worktree 的 .git 檔案必須指回這個倉庫
+    for wt in worktrees.iter().filter(|wt| wt.path.join(".git").is_file()) {
+        let points_here = read_gitdir_pointer(&wt.path)
+            .is_some_and(|target| is_inside(&target, &admin_root));
+        if !points_here {
+            issues.push(Issue::BrokenLink { path: wt.path.clone() });
+        }
+    }
+
+    // worktree 根目錄下未登記的目錄
+    let registered: Vec<PathBuf> = worktrees.iter().map(|wt| canonical(&wt.path)).collect();
+    let worktree_root = generate_worktree_path(&ctx.root, ctx.path_template(), &ctx.project_name, "branch")
+        .parent()
+        .map(Path::to_path_buf);
+    if let Some(entries) = worktree_root.and_then(|root| fs::read_dir(root).ok()) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.join(".git").is_file() || registered.contains(&canonical(&path)) {
+                continue;
+            }
+
+            match read_gitdir_pointer(&path) {
+                Some(target) if target.is_dir() && is_inside(&target, &admin_root) => {
+                    issues.push(Issue::MovedWorktree { path });
+                }
+                _ => {
+                    let branch = matching_branch(ctx, &path, &worktrees);
+                    issues.push(Issue::OrphanedDirectory { path, branch });
+                }
+            }
+        }
+    }
+
+    // 目錄已不存在的 worktree；被移動的 worktree 由 repair 一併修復，不另外列出
+    let moved_from: Vec<PathBuf> = issues.iter()
+        .filter_map(|issue| match issue {
+            Issue::MovedWorktree { path } => registered_path(path),
+            _ => None,
+        })
+        .collect();
+    for wt in worktrees.iter().filter(|wt| !wt.path.exists()) {
+        if moved_from.contains(&wt.path) {
+            continue;
+        }
+        issues.push(Issue::MissingDirectory { path: wt.path.clone(), branch: wt.display_name() });
+    }
+
+    // 沒有 worktree 使用的分支（預設分支除外）
+    let branch_manager = BranchManager::new(&ctx.repo);
+    let default_branch = branch_manager.default_branch();
+    let default_tip = default_branch.as_deref().and_then(|name| branch_manager.local_branch_tip(name));
+    for branch in branch_manager.list_local_branches()? {
+        let orphaned = issues.iter().any(|issue| matches!(issue,
+            Issue::OrphanedDirectory { branch: Some(name), .. } if *name == branch.name));
+        if orphaned || Some(&branch.name) == default_branch.as_ref() || worktrees.iter().any(|wt| wt.branch == branch.name) {
+            continue;
+        }
+        let merged = match (branch_manager.local_branch_tip(&branch.name), &default_tip) {
+            (Some(tip), Some(default_tip)) => is_ancestor(ctx, &tip, default_tip),
+            _ => false,
+        };
+        issues.push(Issue::UnusedBranch { branch: branch.name, merged });
+    }
+
+    Ok(issues)
+}
+
+fn apply_fix(ctx: &RepoContext, issue: &Issue, fix: Fix) -> Result<()> {
+    let worktree_manager = ctx.worktree_manager();
+
+    match (issue, fix) {
+        (_, Fix::Prune) => worktree_manager.prune(),
+        (Issue::BrokenLink { path }, Fix::Repair) => worktree_manager.repair_links(path),
+        (Issue::MovedWorktree { path }, Fix::Repair) => {
+            let from = registered_path(path);
+            worktree_manager.repair(path)?;
+            // 記錄為移動，'wt undo' 可以把它移回原本登記的位置
+            if let (Some(from), false) = (from, dry_run::is_enabled()) {
+                let worktrees = worktree_manager.list_worktrees()?;
+                let moved = worktree_manager.find_worktree(&worktrees, &path.to_string_lossy())?;
+                record_move(&ctx.repo, &from, &moved);
+            }
+            Ok(())
+        }
+        (Issue::StaleAdminEntry { admin_dir }, Fix::DeleteAdminEntry) => delete_directory(admin_dir),
+        (Issue::OrphanedDirectory { path, .. }, Fix::DeleteDirectory) => delete_directory(path),
+        (Issue::OrphanedDirectory { path, branch: Some(branch) }, Fix::Reregister) => reregister(ctx, path, branch),
+        (Issue::UnusedBranch { branch, .. }, Fix::DeleteBranch) => {
+            BranchManager::new(&ctx.repo).delete_local_branch(branch)
+        }
+        _ => Ok(()),
+    }
+}
+
+fn delete_directory(path: &Path) -> Result<()> {
+    // 可能已被先前的 prune 一併清除
+    if !path.exists() {
+        return Ok(());
+    }
+    if dry_run::is_enabled() {
+        dry_run::record(PlannedAction::DeletePath { path: path.to_path_buf() });
+        return Ok(());
+    }
+    fs::remove_dir_all(path)?;
+    Ok(())
+}
+
+// 將目錄移到一旁，在原路徑建立 worktree，再把原本的檔案搬回來
+fn reregister(ctx: &RepoContext, path: &Path, branch: &str) -> Result<()> {
+    if dry_run::is_enabled() {
+        dry_run::record(PlannedAction::RegisterWorktree { path: path.to_path_buf(), branch: branch.to_string() });
+        return Ok(());
+    }
+
+    let worktree_manager = ctx.worktree_manager();
+    let staging = path.with_extension("wt-doctor");
+    fs::rename(path, &staging)?;
+
+    if let Err(e) = worktree_manager.add_worktree_from_directory(&staging, path, branch, &BranchMode::ExistingBranch, None) {
+        if !path.exists() {
+            fs::rename(&staging, path)?;
+        }
+        return Err(e);
+    }
+
+    fs::remove_dir_all(&staging)?;
+
+    let worktrees = worktree_manager.list_worktrees()?;
+    let registered = worktree_manager.find_worktree(&worktrees, &path.to_string_lossy())?;
+    record_operation(&ctx.repo, Operation::Add, &registered, false, None);
+    Ok(())
+}
+
+// 讀取 worktree 中 .git 檔案的 "gitdir: <path>"
+fn read_gitdir_pointer(worktree: &Path) -> Option<PathBuf> {
+    let content = fs::read_to_string(worktree.join(".git")).ok()?;
+    let target = content.lines().find_map(|line| line.strip_prefix("gitdir:"))?.trim();
+    Some(worktree.join(target))
+}
+
+// 被移動的 worktree 原本登記的路徑（admin 目錄中 gitdir 檔案的上層）
+fn registered_path(worktree: &Path) -> Option<PathBuf> {
+    let admin_dir = read_gitdir_pointer(worktree)?;
+    let gitdir = fs::read_to_string(admin_dir.join("gitdir")).ok()?;
+    Path::new(gitdir.trim()).parent().map(Path::to_path_buf)
+}
+
+// 依目前的路徑模板找出應位於此路徑、且尚未有 worktree 的本地分支
+fn matching_branch(ctx: &RepoContext, path: &Path, worktrees: &[WorktreeInfo]) -> Option<String> {
+    let path = canonical(path);
+    BranchManager::new(&ctx.repo).list_local_branches().ok()?
+        .into_iter()
+        .filter(|branch| !worktrees.iter().any(|wt| wt.branch == branch.name))
+        .find(|branch| {
+            let expected = generate_worktree_path(&ctx.root, ctx.path_template(), &ctx.project_name, &branch.name);
+            canonical(&expected) == path
+        })
+        .map(|branch| branch.name)
+}
+
+fn is_ancestor(ctx: &RepoContext, commit: &str, descendant: &str) -> bool {
+    let (Ok(commit), Ok(descendant)) = (git2::Oid::from_str(commit), git2::Oid::from_str(descendant)) else {
+        return false;
+    };
+    commit == descendant || ctx.repo.inner.graph_descendant_of(descendant, commit).unwrap_or(false)
+}
+
+fn is_inside(path: &Path, directory: &Path) -> bool {
+    canonical(path).starts_with(canonical(directory))
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
\ No newline at end of file
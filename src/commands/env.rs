@@ -0,0 +1,62 @@
+use clap::Args;
+use anyhow::Result;
+use serde_json::json;
+use crate::git::context::RepoContext;
+use crate::git::environment::{shell_exports, EnvManager};
+use crate::ui::colors::ColorizeExt;
+use crate::ui::display::Display;
+use crate::ui::prompts::Prompts;
+
+#[derive(Debug, Args)]
+pub struct EnvCommand {
+    /// Worktree to show (branch name, directory name or path); defaults to the current worktree
+    pub worktree: Option<String>,
+
+    /// Print export statements for eval "$(wt env --shell)"
+    #[arg(long)]
+    pub shell: bool,
+}
+
+impl EnvCommand {
+    pub fn execute(&self) -> Result<()> {
+        let ctx = RepoContext::discover()?;
+        let worktree_manager = ctx.worktree_manager();
+        let worktrees = worktree_manager.list_worktrees()?;
+
+        let worktree = match (&self.worktree, worktrees.iter().find(|wt| wt.is_current)) {
+            (Some(query), _) => worktree_manager.find_worktree(&worktrees, query)?,
+            (None, Some(current)) => current.clone(),
+            (None, None) => Prompts::select_worktree(worktrees.clone(), &ctx.config.picker)?,
+        };
+
+        let env_manager = EnvManager::new(&ctx.repo, &ctx.config.env, &ctx.project_name);
+        if !env_manager.is_configured() {
+            Display::show_info("No variables configured; add them under [env.vars] in .wt.toml");
+            Display::show_result("env", &json!({ "worktree": worktree, "vars": {} }));
+            return Ok(());
+        }
+
+        // 建立 worktree 之前就存在的設定也在這裡補上分配
+        let vars = env_manager.ensure(&worktree.path, &worktree.name())?;
+        let file = env_manager.file_path(&worktree.path);
+
+        if self.shell {
+            println!("{}", shell_exports(&vars));
+            return Ok(());
+        }
+
+        if Display::is_human() {
+            for (name, value) in &vars {
+                println!("{}={}", name.info(), value);
+            }
+            println!("{}", format!("# written to {}", file.display()).dim());
+        }
+        Display::show_result("env", &json!({
+            "worktree": worktree,
+            "file": file,
+            "vars": vars,
+        }));
+
+        Ok(())
+    }
+}
\ No newline at end of file
@@ -1,3 +1,14 @@
 pub mod add;
 pub mod remove;
-pub mod open;
\ No newline at end of file
+pub mod open;
+pub mod status;
+pub mod clone;
+pub mod completions;
+pub mod undo;
+pub mod snapshots;
+pub mod archive;
+pub mod doctor;
+pub mod adopt;
+pub mod stack;
+pub mod env;
+pub mod sparse;
\ No newline at end of file
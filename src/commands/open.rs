@@ -1,36 +1,84 @@
 use clap::Args;
-use anyhow::Result;
-use crate::git::repository::Repository;
-use crate::git::worktree::WorktreeManager;
+use std::path::Path;
+use anyhow::{Result, anyhow};
+use serde_json::{json, Value};
+use crate::config::Opener;
+use crate::git::context::RepoContext;
 use crate::ui::prompts::Prompts;
 use crate::ui::display::Display;
 use crate::utils::system::open_vscode;
+use crate::utils::tmux;
 
 #[derive(Debug, Args)]
-pub struct OpenCommand {}
+pub struct OpenCommand {
+    /// Worktree to open (branch name, directory name or path); prompts when omitted
+    pub worktree: Option<String>,
+
+    /// Open with this instead of [open] opener from .wt.toml
+    #[arg(long = "with", value_enum, value_name = "OPENER")]
+    pub opener: Option<Opener>,
+}
 
 impl OpenCommand {
     pub fn execute(&self) -> Result<()> {
-        Display::show_info("Opening a worktree in VSCode...");
-        
-        let repo = Repository::open_current()?;
-        let worktree_manager = WorktreeManager::new(repo.inner.path().parent().unwrap().to_path_buf());
+        let ctx = RepoContext::discover()?;
+        let opener = self.opener.unwrap_or(ctx.config.open.opener);
+        let worktree_manager = ctx.worktree_manager();
         
         // 獲取 worktree 列表
         let worktrees = worktree_manager.list_worktrees()?;
         
         if worktrees.is_empty() {
             Display::show_info("No worktrees found to open.");
+            Display::show_result("open", &json!({ "worktree": null }));
             return Ok(());
         }
         
         // 選擇要開啟的 worktree
-        let selected_worktree = Prompts::select_worktree(worktrees)?;
+        let selected_worktree = match &self.worktree {
+            Some(query) => worktree_manager.find_worktree(&worktrees, query)?,
+            None => Prompts::select_worktree(worktrees, &ctx.config.picker)?,
+        };
         
-        Display::show_info("Opening VSCode...");
-        open_vscode(&selected_worktree.path)?;
-        Display::show_success(&format!("VSCode opened for worktree '{}'!", selected_worktree.branch));
+        if !selected_worktree.path.exists() {
+            return Err(anyhow!("Worktree directory {} no longer exists; run 'wt doctor' to clean it up",
+                selected_worktree.path.display()));
+        }
+
+        let result = open_worktree(&ctx, opener, &selected_worktree.path,
+            &selected_worktree.name(), &selected_worktree.display_name(),
+            json!({ "worktree": selected_worktree }))?;
+        Display::show_result("open", &result);
         
         Ok(())
     }
+}
+
+// 以指定的開啟方式開啟 worktree，並把開啟資訊加進 JSON 結果
+pub fn open_worktree(ctx: &RepoContext, opener: Opener, path: &Path, name: &str, label: &str,
+                     mut result: Value) -> Result<Value> {
+    let launched = match opener {
+        Opener::Vscode => {
+            Display::show_info("Opening VSCode...");
+            open_vscode(path)?;
+            Display::show_success(&format!("VSCode opened for worktree '{}'!", label));
+            json!({ "opener": "vscode" })
+        }
+        Opener::Tmux => {
+            let session = tmux::session_name(&ctx.project_name, name);
+            let created = tmux::open(&ctx.config.open.tmux, &session, path)?;
+            let action = if created { "Created" } else { "Reused" };
+            Display::show_success(&format!("{} tmux {} '{}' for worktree '{}'",
+                action, tmux::mode_name(ctx.config.open.tmux.mode), session, label));
+            json!({
+                "opener": "tmux",
+                "tmux": { "name": session, "created": created },
+            })
+        }
+    };
+
+    if let (Some(result), Some(launched)) = (result.as_object_mut(), launched.as_object()) {
+        result.extend(launched.clone());
+    }
+    Ok(result)
 }
\ No newline at end of file
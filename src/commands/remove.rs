@@ -1,38 +1,74 @@
 use clap::Args;
 use anyhow::Result;
-use crate::git::repository::Repository;
-use crate::git::worktree::WorktreeManager;
+use serde_json::json;
+use crate::git::context::RepoContext;
+use crate::git::journal::{record_operation, Operation};
+use crate::git::snapshots::SnapshotManager;
 use crate::ui::prompts::Prompts;
 use crate::ui::display::Display;
+use crate::utils::dry_run;
+use crate::utils::tmux;
 
 #[derive(Debug, Args)]
-pub struct RemoveCommand {}
+pub struct RemoveCommand {
+    /// Worktree to remove (branch name, directory name or path); prompts when omitted
+    pub worktree: Option<String>,
+
+    /// Remove even with uncommitted changes, without asking; the changes are saved as a snapshot first
+    #[arg(short, long)]
+    pub force: bool,
+}
 
 impl RemoveCommand {
     pub fn execute(&self) -> Result<()> {
         Display::show_info("Removing a worktree...");
         
-        let repo = Repository::open_current()?;
-        let worktree_manager = WorktreeManager::new(repo.inner.path().parent().unwrap().to_path_buf());
+        let ctx = RepoContext::discover()?;
+        let worktree_manager = ctx.worktree_manager();
         
         // 獲取 worktree 列表
         let worktrees = worktree_manager.list_worktrees()?;
         
         if worktrees.is_empty() {
             Display::show_info("No worktrees found to remove.");
+            Display::show_result("remove", &json!({ "removed": [] }));
             return Ok(());
         }
         
         // 選擇要移除的 worktree
-        let selected_worktree = Prompts::select_worktree(worktrees)?;
+        let selected_worktree = match &self.worktree {
+            Some(query) => worktree_manager.find_worktree(&worktrees, query)?,
+            None => Prompts::select_worktree(worktrees, &ctx.config.picker)?,
+        };
         
-        // 確認移除
-        if Prompts::confirm_removal(&selected_worktree)? {
+        // 確認移除（乾跑或 --force 時不需要確認）
+        if self.force || dry_run::is_enabled() || Prompts::confirm_removal(&selected_worktree)? {
+            // 強制移除前先將未提交的變更保存為快照
+            let snapshot = if self.force && worktree_manager.is_dirty(&selected_worktree.path).unwrap_or(false) {
+                let snapshot = SnapshotManager::new(&ctx.repo).create(&selected_worktree.path, &selected_worktree.name())?;
+                if !dry_run::is_enabled() {
+                    Display::show_info(&format!("Saved uncommitted changes as snapshot '{}'", snapshot.name));
+                }
+                Some(snapshot.name)
+            } else {
+                None
+            };
+
             Display::show_info("Removing worktree...");
-            worktree_manager.remove_worktree(&selected_worktree.path)?;
-            Display::show_success(&format!("Worktree '{}' removed successfully!", selected_worktree.branch));
+            worktree_manager.remove_worktree(&selected_worktree.path, self.force)?;
+            record_operation(&ctx.repo, Operation::Remove, &selected_worktree, false, snapshot.as_deref());
+            let session = tmux::session_name(&ctx.project_name, &selected_worktree.name());
+            if let Err(e) = tmux::kill(&ctx.config.open.tmux, &session) {
+                Display::show_warning(&format!("Failed to close tmux '{}': {}", session, e));
+            }
+            Display::show_success(&format!("Worktree '{}' removed successfully!", selected_worktree.display_name()));
+            if !dry_run::is_enabled() {
+                Display::show_info("Run 'wt undo' to restore it.");
+            }
+            Display::show_result("remove", &json!({ "removed": [selected_worktree], "snapshot": snapshot }));
         } else {
             Display::show_info("Operation cancelled.");
+            Display::show_result("remove", &json!({ "removed": [], "cancelled": true }));
         }
         
         Ok(())
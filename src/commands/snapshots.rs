@@ -0,0 +1,81 @@
+use clap::{Args, Subcommand};
+use anyhow::Result;
+use serde_json::json;
+use crate::git::context::RepoContext;
+use crate::git::snapshots::{Snapshot, SnapshotManager};
+use crate::ui::colors::ColorizeExt;
+use crate::ui::display::Display;
+use crate::ui::prompts::Prompts;
+use crate::utils::time::format_relative_time;
+
+#[derive(Debug, Args)]
+pub struct SnapshotsCommand {
+    #[command(subcommand)]
+    pub action: Option<SnapshotAction>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum SnapshotAction {
+    /// Apply a snapshot's staged, unstaged and untracked changes to a worktree
+    Apply {
+        /// Snapshot to apply, as listed by 'wt snapshots'
+        snapshot: String,
+
+        /// Worktree to apply it to (branch name, directory name or path); defaults to the current one
+        worktree: Option<String>,
+    },
+}
+
+impl SnapshotsCommand {
+    pub fn execute(&self) -> Result<()> {
+        let ctx = RepoContext::discover()?;
+        let snapshot_manager = SnapshotManager::new(&ctx.repo);
+
+        match &self.action {
+            None => show_snapshots(&snapshot_manager.list()?),
+            Some(SnapshotAction::Apply { snapshot, worktree }) => {
+                let snapshot = snapshot_manager.find(snapshot)?;
+                let worktree_manager = ctx.worktree_manager();
+                let worktrees = worktree_manager.list_worktrees()?;
+
+                // 未指定時套用到目前所在的 worktree；在 bare hub 中則讓使用者選擇
+                let target = match (worktree, &ctx.current_worktree) {
+                    (Some(query), _) => worktree_manager.find_worktree(&worktrees, query)?,
+                    (None, Some(current)) => worktree_manager.find_worktree(&worktrees, &current.to_string_lossy())?,
+                    (None, None) => Prompts::select_worktree(worktrees, &ctx.config.picker)?,
+                };
+
+                snapshot_manager.apply(&snapshot, &target.path)?;
+                Display::show_success(&format!("Applied snapshot '{}' to {}", snapshot.name, target.path.display()));
+                Display::show_result("snapshots", &json!({ "applied": snapshot, "worktree": target.path }));
+                Ok(())
+            }
+        }
+    }
+}
+
+fn show_snapshots(snapshots: &[Snapshot]) -> Result<()> {
+    if !Display::is_human() {
+        Display::show_result("snapshots", &json!({ "snapshots": snapshots }));
+        return Ok(());
+    }
+
+    if snapshots.is_empty() {
+        Display::show_info("No snapshots found.");
+        return Ok(());
+    }
+
+    let name_width = snapshots.iter().map(|snapshot| snapshot.name.chars().count()).max().unwrap_or(0);
+
+    // 最新的在最上面
+    for snapshot in snapshots.iter().rev() {
+        println!(
+            "{:<name_width$}  {}  {}",
+            snapshot.name,
+            snapshot.short_commit(),
+            format_relative_time(snapshot.timestamp).dim(),
+        );
+    }
+
+    Ok(())
+}
\ No newline at end of file
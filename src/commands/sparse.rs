@@ -0,0 +1,186 @@
+use clap::{Args, Subcommand};
+use anyhow::{Result, anyhow};
+use serde_json::json;
+use crate::config::SparseConfig;
+use crate::git::context::RepoContext;
+use crate::git::worktree::WorktreeInfo;
+use crate::ui::display::Display;
+use crate::ui::prompts::Prompts;
+
+#[derive(Debug, Args)]
+pub struct SparseCommand {
+    #[command(subcommand)]
+    pub action: SparseAction,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum SparseAction {
+    /// Check out more directories in a sparse worktree
+    Add {
+        /// Directories to add, relative to the repository root
+        #[arg(required = true)]
+        directories: Vec<String>,
+
+        /// Worktree to change (defaults to the current worktree)
+        #[arg(long, short)]
+        worktree: Option<String>,
+    },
+    /// Stop checking out directories in a sparse worktree
+    Remove {
+        /// Directories to remove, relative to the repository root
+        #[arg(required = true)]
+        directories: Vec<String>,
+
+        /// Worktree to change (defaults to the current worktree)
+        #[arg(long, short)]
+        worktree: Option<String>,
+    },
+    /// List the directories checked out in a sparse worktree
+    List {
+        /// Worktree to show (defaults to the current worktree)
+        #[arg(long, short)]
+        worktree: Option<String>,
+    },
+}
+
+impl SparseCommand {
+    pub fn execute(&self) -> Result<()> {
+        let ctx = RepoContext::discover()?;
+        let worktree_manager = ctx.worktree_manager();
+
+        let query = match &self.action {
+            SparseAction::Add { worktree, .. } | SparseAction::Remove { worktree, .. } | SparseAction::List { worktree } => worktree,
+        };
+        let worktree = select_worktree(&ctx, query.as_deref())?;
+
+        let current = worktree_manager.sparse_directories(&worktree.path)?;
+        let Some(current) = current else {
+            if matches!(self.action, SparseAction::List { .. }) {
+                Display::show_info(&format!("Worktree '{}' is a full checkout", worktree.display_name()));
+                Display::show_result("sparse", &json!({ "worktree": worktree, "sparse": false, "directories": [] }));
+                return Ok(());
+            }
+            return Err(anyhow!("Worktree '{}' is not a sparse checkout; create one with 'wt add <branch> --sparse <dir>'",
+                worktree.display_name()));
+        };
+
+        let directories = match &self.action {
+            SparseAction::List { .. } => {
+                if Display::is_human() {
+                    for directory in &current {
+                        println!("{}", directory);
+                    }
+                }
+                Display::show_result("sparse", &json!({ "worktree": worktree, "sparse": true, "directories": current }));
+                return Ok(());
+            }
+            SparseAction::Add { directories, .. } => {
+                let mut updated = current.clone();
+                for directory in normalize_directories(directories)? {
+                    if !updated.contains(&directory) {
+                        updated.push(directory);
+                    }
+                }
+                updated
+            }
+            SparseAction::Remove { directories, .. } => {
+                let removed = normalize_directories(directories)?;
+                if let Some(missing) = removed.iter().find(|dir| !current.contains(dir)) {
+                    return Err(anyhow!("'{}' is not checked out in worktree '{}'", missing, worktree.display_name()));
+                }
+                current.iter().filter(|dir| !removed.contains(dir)).cloned().collect()
+            }
+        };
+
+        worktree_manager.set_sparse(&worktree.path, &directories)?;
+        Display::show_success(&format!("Worktree '{}' now checks out: {}", worktree.display_name(),
+            if directories.is_empty() { "only the root files".to_string() } else { directories.join(", ") }));
+        Display::show_result("sparse", &json!({ "worktree": worktree, "sparse": true, "directories": directories }));
+
+        Ok(())
+    }
+}
+
+fn select_worktree(ctx: &RepoContext, query: Option<&str>) -> Result<WorktreeInfo> {
+    let worktree_manager = ctx.worktree_manager();
+    let worktrees = worktree_manager.list_worktrees()?;
+    match (query, worktrees.iter().find(|wt| wt.is_current)) {
+        (Some(query), _) => worktree_manager.find_worktree(&worktrees, query),
+        (None, Some(current)) => Ok(current.clone()),
+        (None, None) => Prompts::select_worktree(worktrees, &ctx.config.picker),
+    }
+}
+
+// cone 模式只接受相對於倉庫根目錄的目錄
+pub(crate) fn normalize_directories(directories: &[String]) -> Result<Vec<String>> {
+    let mut normalized = Vec::new();
+    for directory in directories {
+        let trimmed = directory.trim().trim_start_matches("./").trim_end_matches('/');
+        if trimmed.is_empty() || trimmed == "." {
+            return Err(anyhow!("'{}' is not a directory inside the repository", directory));
+        }
+        if trimmed.starts_with('/') || trimmed.split('/').any(|part| part == "..") {
+            return Err(anyhow!("Sparse directories must be relative to the repository root: '{}'", directory));
+        }
+        if trimmed.contains(['*', '?', '[', '!']) {
+            return Err(anyhow!("Sparse directories cannot contain patterns: '{}'", directory));
+        }
+        if !normalized.iter().any(|dir: &String| dir == trimmed) {
+            normalized.push(trimmed.to_string());
+        }
+    }
+    Ok(normalized)
+}
+
+// --sparse-profile 與 --sparse 合併後的目錄清單
+pub(crate) fn resolve_directories(config: &SparseConfig, profiles: &[String], directories: &[String]) -> Result<Vec<String>> {
+    let mut all = Vec::new();
+    for name in profiles {
+        let profile = config.profiles.get(name).ok_or_else(|| {
+            let known: Vec<&str> = config.profiles.keys().map(|k| k.as_str()).collect();
+            if known.is_empty() {
+                anyhow!("Unknown sparse profile '{}'; define it under [sparse.profiles] in .wt.toml", name)
+            } else {
+                anyhow!("Unknown sparse profile '{}'; available: {}", name, known.join(", "))
+            }
+        })?;
+        all.extend(profile.iter().cloned());
+    }
+    all.extend(directories.iter().cloned());
+    normalize_directories(&all)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dirs(directories: &[&str]) -> Vec<String> {
+        directories.iter().map(|dir| dir.to_string()).collect()
+    }
+
+    #[test]
+    fn strips_dot_prefixes_and_trailing_slashes() {
+        let normalized = normalize_directories(&dirs(&["./web/", " api ", "packages/shared//"])).unwrap();
+        assert_eq!(normalized, dirs(&["web", "api", "packages/shared"]));
+    }
+
+    #[test]
+    fn removes_duplicates_keeping_order() {
+        let normalized = normalize_directories(&dirs(&["web", "api", "./web"])).unwrap();
+        assert_eq!(normalized, dirs(&["web", "api"]));
+    }
+
+    #[test]
+    fn rejects_the_root_and_paths_outside_the_repository() {
+        for directory in ["", ".", "./", "/etc", "../other", "web/../../x"] {
+            assert!(normalize_directories(&dirs(&[directory])).is_err(), "{:?} should be rejected", directory);
+        }
+    }
+
+    #[test]
+    fn rejects_patterns() {
+        for directory in ["web/*", "a?", "[ab]", "!web"] {
+            assert!(normalize_directories(&dirs(&[directory])).is_err(), "{:?} should be rejected", directory);
+        }
+    }
+}
\ No newline at end of file
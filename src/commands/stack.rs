@@ -0,0 +1,185 @@
+use clap::Args;
+use anyhow::{Result, anyhow};
+use serde_json::json;
+use std::collections::HashSet;
+use crate::commands::add::choose_worktree_path;
+use crate::git::branches::BranchManager;
+use crate::git::context::RepoContext;
+use crate::git::environment::EnvManager;
+use crate::git::journal::{record_operation, Operation};
+use crate::git::setup::prepare_worktree;
+use crate::git::stack::StackManager;
+use crate::git::worktree::BranchMode;
+use crate::ui::display::Display;
+use crate::ui::prompts::Prompts;
+use crate::utils::dry_run;
+use crate::utils::naming::check_branch_name;
+use crate::utils::system::run_post_add_hooks;
+
+#[derive(Debug, Args)]
+pub struct StackCommand {
+    /// Name of the new branch
+    pub branch: String,
+
+    /// Worktree whose branch to build on (defaults to the current worktree)
+    #[arg(long, value_name = "WORKTREE")]
+    pub on: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct RestackCommand {}
+
+impl StackCommand {
+    pub fn execute(&self) -> Result<()> {
+        let ctx = RepoContext::discover()?;
+        let branch_manager = BranchManager::new(&ctx.repo);
+        let worktree_manager = ctx.worktree_manager();
+        let worktrees = worktree_manager.list_worktrees()?;
+
+        let parent = match (&self.on, worktrees.iter().find(|wt| wt.is_current)) {
+            (Some(query), _) => worktree_manager.find_worktree(&worktrees, query)?,
+            (None, Some(current)) => current.clone(),
+            (None, None) => Prompts::select_worktree(worktrees.clone(), &ctx.config.picker)?,
+        };
+        if parent.is_detached || parent.is_bare {
+            return Err(anyhow!("Worktree '{}' is not on a branch; pick one with --on <worktree>", parent.display_name()));
+        }
+
+        check_branch_name(&self.branch, &ctx.config.naming)?;
+        if branch_manager.local_branch_tip(&self.branch).is_some() {
+            return Err(anyhow!("Branch '{}' already exists", self.branch));
+        }
+
+        let Some(worktree_path) = choose_worktree_path(&ctx, &self.branch, None, &worktrees)? else {
+            Display::show_info("Operation cancelled.");
+            Display::show_result("stack", &json!({ "cancelled": true }));
+            return Ok(());
+        };
+        worktree_manager.add_worktree(&worktree_path, &self.branch, &BranchMode::NewBranch, Some(&parent.branch))?;
+
+        // 記錄父分支與建立時的起點，供 restack 使用
+        let base = branch_manager.local_branch_tip(&parent.branch).unwrap_or_else(|| parent.commit.clone());
+        StackManager::new(&ctx.repo).set_parent(&self.branch, &parent.branch, &base)?;
+
+        Display::show_success(&format!("Worktree for '{}' stacked on '{}' created at: {}",
+            self.branch, parent.branch, worktree_path.display()));
+
+        if !dry_run::is_enabled() {
+            let worktrees = worktree_manager.list_worktrees()?;
+            let created = worktree_manager.find_worktree(&worktrees, &worktree_path.to_string_lossy())?;
+            record_operation(&ctx.repo, Operation::Add, &created, true, None);
+        }
+
+        let setup = prepare_worktree(&ctx.repo, &ctx.config.setup, &worktree_path, &parent.branch);
+        let vars = EnvManager::new(&ctx.repo, &ctx.config.env, &ctx.project_name).ensure(&worktree_path, &self.branch)?;
+        run_post_add_hooks(&ctx.config.hooks.post_add, &worktree_path, &self.branch, &vars)?;
+
+        Display::show_result("stack", &json!({
+            "path": worktree_path,
+            "branch": self.branch,
+            "parent": parent.branch,
+            "env": vars,
+            "setup": setup,
+        }));
+
+        Ok(())
+    }
+}
+
+impl RestackCommand {
+    pub fn execute(&self) -> Result<()> {
+        let ctx = RepoContext::discover()?;
+        let branch_manager = BranchManager::new(&ctx.repo);
+        let worktree_manager = ctx.worktree_manager();
+        let stack_manager = StackManager::new(&ctx.repo);
+
+        let links = stack_manager.ordered_links()?;
+        if links.is_empty() {
+            Display::show_info("No stacked branches; create one with 'wt stack <branch>'.");
+            Display::show_result("restack", &json!({ "branches": [] }));
+            return Ok(());
+        }
+
+        let worktrees = worktree_manager.list_worktrees()?;
+        let mut rebased = HashSet::new();
+        let mut results = Vec::new();
+
+        for link in links {
+            let Some(tip) = branch_manager.local_branch_tip(&link.branch) else {
+                continue;
+            };
+
+            // 父分支已刪除（通常是合併後）時改為疊在預設分支上
+            let parent = match branch_manager.local_branch_tip(&link.parent) {
+                Some(_) => link.parent.clone(),
+                None => {
+                    let default = branch_manager.default_branch()
+                        .ok_or_else(|| anyhow!("Parent '{}' of '{}' no longer exists and there is no default branch", link.parent, link.branch))?;
+                    Display::show_warning(&format!("Parent '{}' of '{}' no longer exists; restacking it onto '{}'",
+                        link.parent, link.branch, default));
+                    stack_manager.set_parent(&link.branch, &default, link.base.as_deref().unwrap_or(&tip))?;
+                    default
+                }
+            };
+            let parent_tip = branch_manager.local_branch_tip(&parent).unwrap_or_default();
+
+            // 已包含父分支的 tip，且記錄的起點仍在父分支的歷史中（父分支被刪除或改寫時不成立）
+            let up_to_date = !rebased.contains(&parent)
+                && contains_commit(&ctx.repo.inner, &tip, &parent_tip)
+                && link.base.as_deref().is_none_or(|base| contains_commit(&ctx.repo.inner, &parent_tip, base));
+            if up_to_date {
+                stack_manager.set_base(&link.branch, &parent_tip)?;
+                results.push(json!({ "branch": link.branch, "parent": parent, "status": "up_to_date" }));
+                continue;
+            }
+
+            let worktree = match worktrees.iter().find(|wt| !wt.is_detached && wt.branch == link.branch) {
+                Some(wt) if wt.path.exists() && !worktree_manager.is_dirty(&wt.path).unwrap_or(true) => wt,
+                found => {
+                    let reason = match found {
+                        None => "it has no worktree",
+                        Some(wt) if !wt.path.exists() => "its worktree directory is missing",
+                        Some(_) => "its worktree has uncommitted changes",
+                    };
+                    Display::show_warning(&format!("Skipped '{}': {}", link.branch, reason));
+                    results.push(json!({ "branch": link.branch, "parent": parent, "status": "skipped", "reason": reason }));
+                    continue;
+                }
+            };
+
+            // 沒有記錄起點時以兩者的 merge base 代替
+            let upstream = match &link.base {
+                Some(base) => base.clone(),
+                None => merge_base(&ctx.repo.inner, &tip, &parent_tip)
+                    .ok_or_else(|| anyhow!("'{}' and '{}' have no common history", link.branch, parent))?,
+            };
+
+            worktree_manager.rebase(&worktree.path, &link.branch, &parent, &upstream)?;
+            stack_manager.set_base(&link.branch, &parent_tip)?;
+            Display::show_success(&format!("Rebased '{}' onto '{}'", link.branch, parent));
+            results.push(json!({ "branch": link.branch, "parent": parent, "status": "rebased" }));
+            rebased.insert(link.branch.clone());
+        }
+
+        if results.iter().all(|result| result["status"] == "up_to_date") {
+            Display::show_info("All stacked branches are up to date.");
+        }
+        Display::show_result("restack", &json!({ "branches": results }));
+
+        Ok(())
+    }
+}
+
+// branch 的歷史中是否已包含 commit
+fn contains_commit(repo: &git2::Repository, branch: &str, commit: &str) -> bool {
+    let (Ok(branch), Ok(commit)) = (git2::Oid::from_str(branch), git2::Oid::from_str(commit)) else {
+        return false;
+    };
+    branch == commit || repo.graph_descendant_of(branch, commit).unwrap_or(false)
+}
+
+fn merge_base(repo: &git2::Repository, a: &str, b: &str) -> Option<String> {
+    let a = git2::Oid::from_str(a).ok()?;
+    let b = git2::Oid::from_str(b).ok()?;
+    repo.merge_base(a, b).ok().map(|oid| oid.to_string())
+}
\ No newline at end of file
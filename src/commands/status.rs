@@ -0,0 +1,161 @@
+use clap::Args;
+use anyhow::Result;
+use serde_json::json;
+use std::collections::HashSet;
+use crate::git::branches::BranchManager;
+use crate::git::context::RepoContext;
+use crate::git::stack::{StackLink, StackManager};
+use crate::git::worktree::WorktreeInfo;
+use crate::ui::colors::{ColorizeExt, Theme};
+use crate::ui::display::Display;
+
+#[derive(Debug, Args)]
+pub struct StatusCommand {
+    /// Show stacked branches (see 'wt stack') as a tree under their parents
+    #[arg(long)]
+    pub tree: bool,
+}
+
+impl StatusCommand {
+    pub fn execute(&self) -> Result<()> {
+        let ctx = RepoContext::discover()?;
+        let worktree_manager = ctx.worktree_manager();
+
+        let worktrees = worktree_manager.list_worktrees()?;
+        let links = StackManager::new(&ctx.repo).links()?;
+
+        for wt in worktrees.iter().filter(|wt| !wt.path.exists()) {
+            Display::show_warning(&format!("Worktree directory is missing: {}", wt.path.display()));
+        }
+
+        if !Display::is_human() {
+            let entries: Vec<_> = worktrees.iter().map(|wt| {
+                json!({
+                    "worktree": wt,
+                    "name": wt.display_name(),
+                    "dirty": worktree_manager.is_dirty(&wt.path).ok(),
+                    "parent": links.iter().find(|link| !wt.is_detached && link.branch == wt.branch).map(|link| &link.parent),
+                })
+            }).collect();
+            Display::show_result("status", &json!({ "worktrees": entries }));
+            return Ok(());
+        }
+
+        if worktrees.is_empty() {
+            Display::show_info("No worktrees found.");
+            return Ok(());
+        }
+
+        let rows = if self.tree {
+            tree_rows(&worktrees, &links)
+        } else {
+            worktrees.iter().map(|wt| Row { prefix: String::new(), node: Node::Worktree(wt) }).collect()
+        };
+
+        let name_width = rows.iter().map(|row| row.prefix.chars().count() + row.name().chars().count()).max().unwrap_or(0);
+        let branch_manager = BranchManager::new(&ctx.repo);
+
+        for row in &rows {
+            let name = format!("{}{}", row.prefix.dim(), row.name());
+            // dim 的控制碼不佔寬度，以實際字數補齊
+            let padding = " ".repeat(name_width - row.prefix.chars().count() - row.name().chars().count());
+
+            match row.node {
+                Node::Worktree(wt) => {
+                    let marker = if wt.is_current { "*".info() } else { " ".to_string() };
+                    let state = match worktree_manager.is_dirty(&wt.path) {
+                        Ok(true) => "dirty".warning(),
+                        Ok(false) => "clean".success(),
+                        Err(_) => "missing".error(),
+                    };
+
+                    println!(
+                        "{} {}{}  {}  {}  {}",
+                        marker,
+                        name,
+                        padding,
+                        wt.short_commit(),
+                        state,
+                        wt.path.to_string_lossy().dim(),
+                    );
+                }
+                Node::Branch(branch) => {
+                    let tip = branch_manager.local_branch_tip(branch).unwrap_or_default();
+                    println!("  {}{}  {}  {}", name, padding, &tip[..tip.len().min(7)], "no worktree".dim());
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+enum Node<'a> {
+    Worktree(&'a WorktreeInfo),
+    // 有子分支但沒有 worktree 的分支
+    Branch(&'a str),
+}
+
+struct Row<'a> {
+    prefix: String,
+    node: Node<'a>,
+}
+
+impl Row<'_> {
+    fn name(&self) -> String {
+        match self.node {
+            Node::Worktree(wt) => wt.display_name(),
+            Node::Branch(branch) => branch.to_string(),
+        }
+    }
+}
+
+// 依 stack 的父子關係排列：沒有父分支的 worktree 為根，子分支縮排在父分支之下
+fn tree_rows<'a>(worktrees: &'a [WorktreeInfo], links: &'a [StackLink]) -> Vec<Row<'a>> {
+    let is_child = |branch: &str| links.iter().any(|link| link.branch == branch);
+    let mut roots: Vec<Node> = worktrees.iter()
+        .filter(|wt| wt.is_detached || !is_child(&wt.branch))
+        .map(Node::Worktree)
+        .collect();
+    for link in links {
+        let has_worktree = worktrees.iter().any(|wt| !wt.is_detached && wt.branch == link.parent);
+        let listed = roots.iter().any(|node| matches!(node, Node::Branch(b) if *b == link.parent));
+        if !has_worktree && !listed && !is_child(&link.parent) {
+            roots.push(Node::Branch(&link.parent));
+        }
+    }
+
+    let mut rows = Vec::new();
+    let mut visited = HashSet::new();
+    for root in roots {
+        push_node(&mut rows, &mut visited, root, String::new(), String::new(), worktrees, links);
+    }
+    rows
+}
+
+fn push_node<'a>(rows: &mut Vec<Row<'a>>, visited: &mut HashSet<String>, node: Node<'a>, prefix: String, indent: String,
+    worktrees: &'a [WorktreeInfo], links: &'a [StackLink]) {
+    let branch = match &node {
+        Node::Worktree(wt) if wt.is_detached => None,
+        Node::Worktree(wt) => Some(wt.branch.clone()),
+        Node::Branch(branch) => Some(branch.to_string()),
+    };
+    rows.push(Row { prefix, node });
+
+    // 設定錯誤造成循環時每個分支只顯示一次
+    let Some(branch) = branch.filter(|branch| visited.insert(branch.clone())) else {
+        return;
+    };
+
+    let theme = Theme::current();
+    let children: Vec<&StackLink> = links.iter().filter(|link| link.parent == branch).collect();
+    for (index, child) in children.iter().enumerate() {
+        let last = index + 1 == children.len();
+        let node = match worktrees.iter().find(|wt| !wt.is_detached && wt.branch == child.branch) {
+            Some(wt) => Node::Worktree(wt),
+            None => Node::Branch(&child.branch),
+        };
+        push_node(rows, visited, node, format!("{}{}", indent, theme.tree_branch(last)),
+            format!("{}{}", indent, theme.tree_indent(last)), worktrees, links);
+    }
+}
\ No newline at end of file
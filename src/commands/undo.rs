@@ -0,0 +1,194 @@
+use clap::Args;
+use anyhow::{Result, anyhow};
+use serde_json::json;
+use crate::git::branches::BranchManager;
+use crate::git::context::RepoContext;
+use crate::git::journal::{Journal, JournalEntry, Operation};
+use crate::git::snapshots::SnapshotManager;
+use crate::git::worktree::BranchMode;
+use crate::ui::colors::ColorizeExt;
+use crate::ui::display::Display;
+use crate::utils::dry_run;
+use crate::utils::time::format_relative_time;
+
+#[derive(Debug, Args)]
+pub struct UndoCommand {
+    /// List recorded operations instead of undoing the latest one
+    #[arg(long)]
+    pub list: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct RestoreCommand {
+    /// Journal id of the removal to restore (see 'wt undo --list')
+    pub id: u64,
+}
+
+impl UndoCommand {
+    pub fn execute(&self) -> Result<()> {
+        let ctx = RepoContext::discover()?;
+        let journal = Journal::open(&ctx.repo);
+
+        if self.list {
+            return show_journal(&journal.entries()?);
+        }
+
+        let entry = journal.last_undoable()?
+            .ok_or_else(|| anyhow!("Nothing to undo"))?;
+
+        match entry.operation {
+            Operation::Add => revert_add(&ctx, &entry)?,
+            Operation::Remove => restore_removed(&ctx, &entry)?,
+            Operation::Move => revert_move(&ctx, &entry)?,
+        }
+
+        if !dry_run::is_enabled() {
+            journal.mark_undone(entry.id)?;
+        }
+        Display::show_result("undo", &json!({ "undone": entry }));
+        Ok(())
+    }
+}
+
+impl RestoreCommand {
+    pub fn execute(&self) -> Result<()> {
+        let ctx = RepoContext::discover()?;
+        let journal = Journal::open(&ctx.repo);
+        let entry = journal.find(self.id)?;
+
+        if entry.operation != Operation::Remove {
+            return Err(anyhow!("Journal entry #{} is not a removal", entry.id));
+        }
+        if entry.undone {
+            return Err(anyhow!("Journal entry #{} has already been restored", entry.id));
+        }
+
+        restore_removed(&ctx, &entry)?;
+
+        if !dry_run::is_enabled() {
+            journal.mark_undone(entry.id)?;
+        }
+        Display::show_result("restore", &json!({ "restored": entry }));
+        Ok(())
+    }
+}
+
+// 復原新增：移除 worktree；若分支是當時建立且之後沒有新的 commit，一併刪除
+fn revert_add(ctx: &RepoContext, entry: &JournalEntry) -> Result<()> {
+    let worktree_manager = ctx.worktree_manager();
+    let worktrees = worktree_manager.list_worktrees()?;
+
+    match worktree_manager.find_worktree(&worktrees, &entry.path.to_string_lossy()) {
+        Ok(worktree) => worktree_manager.remove_worktree(&worktree.path, false)?,
+        Err(_) => Display::show_warning(&format!("Worktree at {} no longer exists", entry.path.display())),
+    }
+
+    if let (true, Some(branch)) = (entry.created_branch, &entry.branch) {
+        let branch_manager = BranchManager::new(&ctx.repo);
+        match branch_manager.local_branch_tip(branch) {
+            Some(tip) if tip == entry.commit => {
+                branch_manager.delete_local_branch(branch)?;
+            }
+            Some(_) => Display::show_warning(&format!("Keeping branch '{}' because it has new commits", branch)),
+            None => {}
+        }
+    }
+
+    Display::show_success(&format!("Undid adding worktree '{}' at {}", entry.name(), entry.path.display()));
+    Ok(())
+}
+
+// 復原移動：把 worktree 移回原本的路徑
+fn revert_move(ctx: &RepoContext, entry: &JournalEntry) -> Result<()> {
+    let from = entry.moved_from.as_ref()
+        .ok_or_else(|| anyhow!("Journal entry #{} does not record where the worktree was moved from", entry.id))?;
+    if from.exists() {
+        return Err(anyhow!("Cannot move '{}' back: {} already exists", entry.name(), from.display()));
+    }
+
+    let worktree_manager = ctx.worktree_manager();
+    let worktrees = worktree_manager.list_worktrees()?;
+    let worktree = worktree_manager.find_worktree(&worktrees, &entry.path.to_string_lossy())
+        .map_err(|_| anyhow!("Cannot move '{}' back: there is no worktree at {} anymore", entry.name(), entry.path.display()))?;
+    worktree_manager.move_worktree(&worktree.path, from)?;
+
+    Display::show_success(&format!("Moved worktree '{}' back to {}", entry.name(), from.display()));
+    Ok(())
+}
+
+// 在原本的路徑重新建立 worktree；分支已被刪除時由紀錄的 commit 重新建立
+fn restore_removed(ctx: &RepoContext, entry: &JournalEntry) -> Result<()> {
+    if entry.path.exists() {
+        return Err(anyhow!("Cannot restore '{}': {} already exists", entry.name(), entry.path.display()));
+    }
+
+    let worktree_manager = ctx.worktree_manager();
+    match &entry.branch {
+        Some(branch) if BranchManager::new(&ctx.repo).local_branch_tip(branch).is_some() => {
+            worktree_manager.add_worktree(&entry.path, branch, &BranchMode::ExistingBranch, None)?;
+        }
+        Some(branch) => {
+            Display::show_info(&format!("Recreating branch '{}' at {}", branch, entry.short_commit()));
+            worktree_manager.add_worktree(&entry.path, branch, &BranchMode::NewBranch, Some(&entry.commit))?;
+        }
+        None => {
+            worktree_manager.add_worktree(&entry.path, &entry.commit, &BranchMode::Detached, None)?;
+        }
+    }
+
+    // 強制移除時保存的未提交變更一併還原
+    if let Some(name) = &entry.snapshot {
+        let snapshot_manager = SnapshotManager::new(&ctx.repo);
+        match snapshot_manager.find(name) {
+            Ok(snapshot) => {
+                snapshot_manager.apply(&snapshot, &entry.path)?;
+                Display::show_info(&format!("Applied snapshot '{}'", snapshot.name));
+            }
+            Err(e) => Display::show_warning(&e.to_string()),
+        }
+    }
+
+    Display::show_success(&format!("Restored worktree '{}' at {}", entry.name(), entry.path.display()));
+    Ok(())
+}
+
+fn show_journal(entries: &[JournalEntry]) -> Result<()> {
+    if !Display::is_human() {
+        Display::show_result("undo", &json!({ "entries": entries }));
+        return Ok(());
+    }
+
+    if entries.is_empty() {
+        Display::show_info("No operations recorded.");
+        return Ok(());
+    }
+
+    let name_width = entries.iter().map(|entry| entry.name().chars().count()).max().unwrap_or(0);
+
+    // 最新的在最上面
+    for entry in entries.iter().rev() {
+        let operation = match entry.operation {
+            Operation::Add => "add   ",
+            Operation::Remove => "remove",
+            Operation::Move => "move  ",
+        };
+        let path = match &entry.moved_from {
+            Some(from) => format!("{} -> {}", from.display(), entry.path.display()),
+            None => entry.path.display().to_string(),
+        };
+        let state = if entry.undone { "undone".dim() } else { String::new() };
+
+        println!(
+            "#{:<4} {:<14} {}  {:<name_width$}  {}  {}  {}",
+            entry.id,
+            format_relative_time(entry.timestamp),
+            operation,
+            entry.name(),
+            entry.short_commit(),
+            path.dim(),
+            state,
+        );
+    }
+
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,332 @@
+use anyhow::{Result, anyhow};
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+pub const CONFIG_FILE_NAME: &str = ".wt.toml";
+
+// 路徑模板相對於主 worktree（或 bare hub）的根目錄，可用 {project} 與 {branch}
+pub const DEFAULT_PATH_TEMPLATE: &str = "../{project}-worktree/{project}-{branch}-worktree";
+pub const DEFAULT_HUB_PATH_TEMPLATE: &str = "{branch}";
+
+// 分支命名模板，可用 {type}、{ticket} 與 {summary}
+pub const DEFAULT_NAMING_TEMPLATE: &str = "{type}/{ticket}-{summary}";
+
+pub const STARTER_CONFIG: &str = r#"# wt configuration — see https://github.com/enohuang3108/worktree
+
+[worktree]
+# Where new worktrees are created, relative to the repository root.
+# Available fields: {project}, {branch}
+# path_template = "../{project}-worktree/{project}-{branch}-worktree"
+
+[naming]
+# Build new branch names from a ticket instead of typing them by hand.
+# Available fields: {type}, {ticket}, {summary} (the summary is slugified)
+# template = "{type}/{ticket}-{summary}"
+# types = ["feat", "fix", "chore", "docs", "refactor", "test"]
+# ticket_pattern = "[A-Z][A-Z0-9]+-[0-9]+"
+# Rules checked for every new branch name
+# prefixes = ["feat/", "fix/", "chore/"]
+# patterns = ["^[a-z]+/[A-Z]+-[0-9]+-"]
+# forbidden_words = ["wip", "tmp"]
+# max_length = 60
+# lowercase = false
+
+[sparse.profiles]
+# Named sets of directories for 'wt add --sparse-profile <name>'
+# web = ["packages/web", "packages/shared"]
+
+[env]
+# Per-worktree variables, written to this file in every new worktree and
+# shown by 'wt env' (use: eval "$(wt env --shell)")
+# file = ".env.wt"
+
+[env.vars]
+# A unique port per worktree, kept stable once allocated
+# PORT = { range = [3000, 3999] }
+# Template fields: {project}, {branch}, {slug} (the branch as lowercase
+# letters and digits joined by '_') and any range variable, e.g. {PORT}
+# DATABASE_NAME = { template = "{project}_{slug}" }
+# API_URL = { template = "http://localhost:{PORT}" }
+
+[setup]
+# After creating a worktree, initialize submodules when .gitmodules exists
+# (reusing the main checkout's submodule objects where possible)
+# submodules = true
+# and run 'git lfs pull' when .gitattributes uses Git LFS
+# lfs = true
+
+[hooks]
+# Commands run inside every newly created worktree
+# post_add = ["npm install"]
+
+[open]
+# How 'wt open' opens a worktree: "vscode" or "tmux"
+# opener = "vscode"
+
+[open.tmux]
+# "session" for one session per worktree, or "window" for a window in the current session
+# mode = "session"
+# Commands started in the panes of a new session; "" leaves a plain shell
+# panes = ["nvim", "cargo watch -x test", ""]
+# Any tmux layout: even-horizontal, even-vertical, main-horizontal, main-vertical, tiled
+# layout = "main-vertical"
+# Kill the session or window when its worktree is removed
+# kill_on_remove = true
+
+[picker]
+# max_items = 50
+# page_size = 15
+
+[theme]
+# Styles are colors and attributes separated by spaces, e.g. "bold green"
+# success = "green"
+# error = "red"
+# info = "blue"
+# warning = "yellow"
+# dim = "dimmed"
+"#;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub picker: PickerConfig,
+    pub worktree: WorktreeConfig,
+    pub naming: NamingConfig,
+    pub open: OpenConfig,
+    pub env: EnvConfig,
+    pub sparse: SparseConfig,
+    pub setup: SetupConfig,
+    pub hooks: HooksConfig,
+    pub theme: ThemeConfig,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct WorktreeConfig {
+    pub path_template: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct NamingConfig {
+    // 未設定時互動模式維持手動輸入分支名稱
+    pub template: Option<String>,
+    // {type} 可選用的類型
+    pub types: Vec<String>,
+    // {ticket} 必須完整符合的正規表示式
+    pub ticket_pattern: String,
+    // 分支名稱必須以其中之一開頭，空白代表不限制
+    pub prefixes: Vec<String>,
+    // 分支名稱必須符合其中之一的正規表示式
+    pub patterns: Vec<String>,
+    // 不可出現在分支名稱中的字詞（不分大小寫，以 / - _ . 分隔）
+    pub forbidden_words: Vec<String>,
+    pub max_length: Option<usize>,
+    // 要求分支名稱全為小寫，由模板產生的名稱會自動轉為小寫
+    pub lowercase: bool,
+}
+
+impl Default for NamingConfig {
+    fn default() -> Self {
+        Self {
+            template: None,
+            types: ["feat", "fix", "chore", "docs", "refactor", "test"].iter().map(|t| t.to_string()).collect(),
+            ticket_pattern: "[A-Z][A-Z0-9]+-[0-9]+".to_string(),
+            prefixes: Vec::new(),
+            patterns: Vec::new(),
+            forbidden_words: Vec::new(),
+            max_length: None,
+            lowercase: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Opener {
+    #[default]
+    Vscode,
+    Tmux,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct OpenConfig {
+    pub opener: Opener,
+    pub tmux: TmuxConfig,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TmuxMode {
+    #[default]
+    Session,
+    Window,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct TmuxConfig {
+    pub mode: TmuxMode,
+    // 每個 pane 啟動時執行的指令，空字串代表一般 shell
+    pub panes: Vec<String>,
+    // tmux 的版面名稱，例如 main-vertical 或 tiled
+    pub layout: Option<String>,
+    // 移除 worktree 時一併關閉對應的 session 或 window
+    pub kill_on_remove: bool,
+}
+
+impl Default for TmuxConfig {
+    fn default() -> Self {
+        Self {
+            mode: TmuxMode::Session,
+            panes: Vec::new(),
+            layout: None,
+            kill_on_remove: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SetupConfig {
+    pub submodules: bool,
+    pub lfs: bool,
+}
+
+impl Default for SetupConfig {
+    fn default() -> Self {
+        Self {
+            submodules: true,
+            lfs: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SparseConfig {
+    // 設定檔名稱對應到 cone 模式的目錄清單
+    pub profiles: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct EnvConfig {
+    // 相對於 worktree 根目錄
+    pub file: String,
+    pub vars: BTreeMap<String, EnvVarConfig>,
+}
+
+impl Default for EnvConfig {
+    fn default() -> Self {
+        Self {
+            file: ".env.wt".to_string(),
+            vars: BTreeMap::new(),
+        }
+    }
+}
+
+// range 與 template 兩者擇一
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EnvVarConfig {
+    pub range: Option<[u16; 2]>,
+    pub template: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct HooksConfig {
+    // 建立 worktree 後在其目錄中執行的指令
+    pub post_add: Vec<String>,
+}
+
+// 樣式格式：以空白分隔的顏色與屬性，例如 "bold green"，"none" 代表不加樣式
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ThemeConfig {
+    pub success: String,
+    pub error: String,
+    pub info: String,
+    pub warning: String,
+    pub dim: String,
+}
+
+impl Default for ThemeConfig {
+    fn default() -> Self {
+        Self {
+            success: "green".to_string(),
+            error: "red".to_string(),
+            info: "blue".to_string(),
+            warning: "yellow".to_string(),
+            dim: "dimmed".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct PickerConfig {
+    // 選單最多顯示的項目數，超過時提供 "show all" 選項
+    pub max_items: usize,
+    // 每頁顯示的行數
+    pub page_size: usize,
+}
+
+impl Default for PickerConfig {
+    fn default() -> Self {
+        Self {
+            max_items: 50,
+            page_size: 15,
+        }
+    }
+}
+
+impl Config {
+    pub fn path_template(&self, hub_directory: bool) -> &str {
+        match &self.worktree.path_template {
+            Some(template) => template,
+            None if hub_directory => DEFAULT_HUB_PATH_TEMPLATE,
+            None => DEFAULT_PATH_TEMPLATE,
+        }
+    }
+
+    pub fn write_starter(root: &Path) -> Result<bool> {
+        let path = root.join(CONFIG_FILE_NAME);
+        if path.exists() {
+            return Ok(false);
+        }
+        fs::write(&path, STARTER_CONFIG)?;
+        Ok(true)
+    }
+
+    pub fn load(root: &Path) -> Result<Self> {
+        let path = root.join(CONFIG_FILE_NAME);
+        if !path.exists() {
+            return Ok(Config::default());
+        }
+
+        let content = fs::read_to_string(&path)?;
+        let config: Config = toml::from_str(&content)
+            .map_err(|e| anyhow!("Failed to parse {}: {}", path.display(), e))?;
+
+        // 變數名稱會直接寫進 shell 的 export 敘述，只接受合法的識別字
+        if let Some(name) = config.env.vars.keys().find(|name| !is_env_var_name(name)) {
+            return Err(anyhow!(
+                "Invalid variable name '{}' in [env.vars] of {}: names must match ^[A-Za-z_][A-Za-z0-9_]*$",
+                name, path.display()));
+        }
+
+        Ok(config)
+    }
+}
+
+fn is_env_var_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
@@ -4,20 +4,27 @@ use std::fmt;
 #[derive(Debug)]
 pub enum WtError {
     NotGitRepository,
-    GitError(String),
-    IOError(String),
     ValidationError(String),
-    UserCancelled,
+    BranchInUse(String),
 }
 
 impl fmt::Display for WtError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             WtError::NotGitRepository => write!(f, "Not in a Git repository"),
-            WtError::GitError(msg) => write!(f, "Git error: {}", msg),
-            WtError::IOError(msg) => write!(f, "IO error: {}", msg),
             WtError::ValidationError(msg) => write!(f, "Validation error: {}", msg),
-            WtError::UserCancelled => write!(f, "Operation cancelled by user"),
+            WtError::BranchInUse(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl WtError {
+    // JSON 輸出中使用的錯誤代碼
+    pub fn code(&self) -> &'static str {
+        match self {
+            WtError::NotGitRepository => "not_git_repository",
+            WtError::ValidationError(_) => "validation_error",
+            WtError::BranchInUse(_) => "branch_in_use",
         }
     }
 }
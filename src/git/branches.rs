@@ -1,12 +1,19 @@
 use anyhow::Result;
 use git2::BranchType;
+use std::path::PathBuf;
 use crate::git::repository::Repository;
+use crate::git::worktree::WorktreeInfo;
+use crate::utils::dry_run::{self, PlannedAction};
+use crate::utils::logger;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Branch {
     pub name: String,
     pub is_remote: bool,
     pub upstream: Option<String>,
+    pub last_commit_time: i64,
+    pub last_author: Option<String>,
+    pub worktree: Option<PathBuf>,
 }
 
 pub struct BranchManager<'a> {
@@ -20,7 +27,7 @@ impl<'a> BranchManager<'a> {
 
     pub fn list_local_branches(&self) -> Result<Vec<Branch>> {
         let mut branches = Vec::new();
-        let branch_iter = self.repo.inner.branches(Some(BranchType::Local))?;
+        let branch_iter = logger::git2("list local branches", || self.repo.inner.branches(Some(BranchType::Local)))?;
         
         for branch_result in branch_iter {
             let (branch, _branch_type) = branch_result?;
@@ -36,10 +43,15 @@ impl<'a> BranchManager<'a> {
                         upstream_branch.name().ok().flatten().map(|s| s.to_string())
                     });
 
+                let (last_commit_time, last_author) = commit_info(&branch);
+
                 branches.push(Branch {
                     name: name.to_string(),
                     is_remote: false,
                     upstream,
+                    last_commit_time,
+                    last_author,
+                    worktree: None,
                 });
             }
         }
@@ -49,7 +61,7 @@ impl<'a> BranchManager<'a> {
 
     pub fn list_remote_branches(&self) -> Result<Vec<Branch>> {
         let mut branches = Vec::new();
-        let branch_iter = self.repo.inner.branches(Some(BranchType::Remote))?;
+        let branch_iter = logger::git2("list remote branches", || self.repo.inner.branches(Some(BranchType::Remote)))?;
         
         for branch_result in branch_iter {
             let (branch, _branch_type) = branch_result?;
@@ -66,10 +78,15 @@ impl<'a> BranchManager<'a> {
                     name.to_string()
                 };
 
+                let (last_commit_time, last_author) = commit_info(&branch);
+
                 branches.push(Branch {
                     name: display_name,
                     is_remote: true,
                     upstream: None,
+                    last_commit_time,
+                    last_author,
+                    worktree: None,
                 });
             }
         }
@@ -90,4 +107,96 @@ impl<'a> BranchManager<'a> {
         
         Ok(all_branches)
     }
+
+    pub fn current_branch(&self) -> Option<String> {
+        let head = self.repo.inner.head().ok()?;
+        if !head.is_branch() {
+            return None;
+        }
+        head.shorthand().map(|s| s.to_string())
+    }
+
+    pub fn default_branch(&self) -> Option<String> {
+        // 優先使用 origin/HEAD 指向的分支
+        if let Ok(reference) = self.repo.inner.find_reference("refs/remotes/origin/HEAD") {
+            if let Some(target) = reference.symbolic_target() {
+                if let Some(name) = target.strip_prefix("refs/remotes/origin/") {
+                    return Some(name.to_string());
+                }
+            }
+        }
+
+        ["main", "master"].iter()
+            .find(|name| self.repo.inner.find_branch(name, BranchType::Local).is_ok())
+            .map(|name| name.to_string())
+    }
+
+    // 本地分支目前指向的 commit；分支不存在時為 None
+    pub fn local_branch_tip(&self, name: &str) -> Option<String> {
+        let branch = self.repo.inner.find_branch(name, BranchType::Local).ok()?;
+        branch.get().target().map(|oid| oid.to_string())
+    }
+
+    pub fn delete_local_branch(&self, name: &str) -> Result<()> {
+        if dry_run::is_enabled() {
+            dry_run::record(PlannedAction::DeleteBranch { branch: name.to_string() });
+            return Ok(());
+        }
+
+        logger::git2(&format!("delete branch {}", name), || {
+            self.repo.inner.find_branch(name, BranchType::Local)?.delete()
+        })?;
+        Ok(())
+    }
+
+    // 將本地分支移到指定的 commit，不存在時建立
+    pub fn set_branch_tip(&self, name: &str, commit: &str) -> Result<()> {
+        if dry_run::is_enabled() {
+            dry_run::record(PlannedAction::UpdateBranch { branch: name.to_string(), commit: commit.to_string() });
+            return Ok(());
+        }
+
+        let oid = git2::Oid::from_str(commit)?;
+        logger::git2(&format!("update branch {}", name), || {
+            let commit = self.repo.inner.find_commit(oid)?;
+            self.repo.inner.branch(name, &commit, true)
+        })?;
+        Ok(())
+    }
+
+    // 標記已經有 worktree 的分支
+    pub fn mark_worktrees(&self, branches: &mut [Branch], worktrees: &[WorktreeInfo]) {
+        for branch in branches.iter_mut().filter(|b| !b.is_remote) {
+            branch.worktree = worktrees.iter()
+                .find(|wt| wt.branch == branch.name)
+                .map(|wt| wt.path.clone());
+        }
+    }
+
+    // 排序：當前分支與預設分支優先，接著本地分支、遠程分支，各自依最近提交時間排序
+    pub fn sort_for_picker(&self, branches: &mut [Branch]) {
+        let current = self.current_branch();
+        let default = self.default_branch();
+
+        branches.sort_by_key(|b| {
+            let pinned = if !b.is_remote && Some(&b.name) == current.as_ref() {
+                0
+            } else if Some(&b.name) == default.as_ref() {
+                1
+            } else {
+                2
+            };
+            (pinned, b.is_remote, std::cmp::Reverse(b.last_commit_time))
+        });
+    }
+}
+
+fn commit_info(branch: &git2::Branch) -> (i64, Option<String>) {
+    match branch.get().peel_to_commit() {
+        Ok(commit) => (
+            commit.time().seconds(),
+            commit.author().name().map(|s| s.to_string()),
+        ),
+        Err(_) => (0, None),
+    }
 }
\ No newline at end of file
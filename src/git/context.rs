@@ -0,0 +1,50 @@
+use anyhow::Result;
+use std::path::PathBuf;
+use crate::config::Config;
+use crate::git::repository::Repository;
+use crate::git::worktree::WorktreeManager;
+
+// 每個指令開始時解析一次的倉庫資訊，不受目前所在的 worktree 或子目錄影響
+pub struct RepoContext {
+    pub repo: Repository,
+    // 主 worktree 的根目錄（bare hub 則為 hub 目錄）
+    pub root: PathBuf,
+    // 目前所在 worktree 的根目錄；在 bare hub 中為 None
+    pub current_worktree: Option<PathBuf>,
+    // 是否為 project/.bare 配置的 hub，決定預設的 worktree 位置
+    pub hub_directory: bool,
+    pub project_name: String,
+    pub config: Config,
+}
+
+impl RepoContext {
+    pub fn discover() -> Result<Self> {
+        let repo = Repository::open_current()?;
+        let root = repo.main_root()?;
+        // 在 linked worktree 中 workdir 為該 worktree，而非 .git/worktrees/<id> 的上層
+        let current_worktree = repo.inner.workdir()
+            .map(|dir| dir.components().collect());
+        let hub_directory = repo.has_hub_directory();
+        let project_name = repo.get_project_name()?;
+        let config = Config::load(&root)?;
+
+        Ok(Self {
+            repo,
+            root,
+            current_worktree,
+            hub_directory,
+            project_name,
+            config,
+        })
+    }
+
+    pub fn worktree_manager(&self) -> WorktreeManager {
+        WorktreeManager::new(self.root.clone())
+            .with_current_worktree(self.current_worktree.clone())
+    }
+
+    pub fn path_template(&self) -> &str {
+        // 沒有 hub 目錄的 bare repository 不能把 worktree 放進 git 資料中，改放在它旁邊
+        self.config.path_template(self.hub_directory)
+    }
+}
\ No newline at end of file
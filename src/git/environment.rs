@@ -0,0 +1,198 @@
+use anyhow::{Result, anyhow};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use crate::config::{EnvConfig, EnvVarConfig};
+use crate::git::repository::Repository;
+use crate::git::worktree::WorktreeManager;
+use crate::utils::dry_run::{self, PlannedAction};
+use crate::utils::logger;
+
+// 放在 git common dir 中，記錄每個 worktree 已分配的值
+pub const ENV_STORE_FILE_NAME: &str = "wt-env.json";
+
+pub type EnvVars = BTreeMap<String, String>;
+
+pub struct EnvManager<'a> {
+    repo: &'a Repository,
+    config: &'a EnvConfig,
+    project: &'a str,
+}
+
+impl<'a> EnvManager<'a> {
+    pub fn new(repo: &'a Repository, config: &'a EnvConfig, project: &'a str) -> Self {
+        Self { repo, config, project }
+    }
+
+    pub fn is_configured(&self) -> bool {
+        !self.config.vars.is_empty()
+    }
+
+    pub fn file_path(&self, worktree: &Path) -> PathBuf {
+        worktree.join(&self.config.file)
+    }
+
+    fn store_path(&self) -> PathBuf {
+        self.repo.inner.commondir().join(ENV_STORE_FILE_NAME)
+    }
+
+    fn load_store(&self) -> BTreeMap<PathBuf, EnvVars> {
+        fs::read_to_string(self.store_path()).ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    // 取得 worktree 的變數，必要時分配新值；保存分配結果並寫入 worktree 中的檔案
+    pub fn ensure(&self, worktree: &Path, branch: &str) -> Result<EnvVars> {
+        if !self.is_configured() {
+            return Ok(EnvVars::new());
+        }
+
+        let key = canonical(worktree);
+        let mut store = self.load_store();
+
+        // 已不存在的 worktree 釋放它們的值
+        let live: HashSet<PathBuf> = WorktreeManager::new(self.repo.main_root()?).list_worktrees()?
+            .iter().map(|wt| canonical(&wt.path)).collect();
+        store.retain(|path, _| path == &key || live.contains(path));
+
+        let previous = store.get(&key).cloned().unwrap_or_default();
+        // 只有範圍變數的值會占用；模板產生的值剛好是數字時不算
+        let taken: HashSet<u16> = store.iter()
+            .filter(|(path, _)| **path != key)
+            .flat_map(|(_, vars)| vars.iter())
+            .filter(|(name, _)| self.config.vars.get(*name).is_some_and(|var| var.range.is_some()))
+            .filter_map(|(_, value)| value.parse().ok())
+            .collect();
+
+        let vars = self.allocate(&previous, &taken, branch)?;
+
+        let file = self.file_path(worktree);
+        if dry_run::is_enabled() {
+            dry_run::record(PlannedAction::WriteFile { path: file });
+            return Ok(vars);
+        }
+
+        store.insert(key, vars.clone());
+        fs::write(self.store_path(), serde_json::to_string_pretty(&store)?)?;
+        fs::write(&file, render_env_file(&vars))?;
+        self.repo.exclude_file(&self.config.file)?;
+        logger::debug(&format!("wrote {} variables to {}", vars.len(), file.display()));
+
+        Ok(vars)
+    }
+
+    fn allocate(&self, previous: &EnvVars, taken: &HashSet<u16>, branch: &str) -> Result<EnvVars> {
+        let mut vars = EnvVars::new();
+        let mut used = taken.clone();
+
+        // 先分配範圍內的值，模板才能引用它們
+        for (name, var) in &self.config.vars {
+            let [low, high] = match var {
+                EnvVarConfig { range: Some(range), template: None } => *range,
+                EnvVarConfig { range: None, template: Some(_) } => continue,
+                _ => return Err(anyhow!("env.vars.{} needs exactly one of 'range' or 'template'", name)),
+            };
+
+            // 原本的值仍在範圍內且沒被占用時沿用，讓值保持穩定
+            let kept = previous.get(name)
+                .and_then(|value| value.parse::<u16>().ok())
+                .filter(|value| (low..=high).contains(value) && !used.contains(value));
+            let value = kept
+                .or_else(|| (low..=high).find(|port| !used.contains(port) && is_port_free(*port)))
+                .ok_or_else(|| anyhow!("No free value left in {}-{} for env.vars.{}", low, high, name))?;
+
+            used.insert(value);
+            vars.insert(name.clone(), value.to_string());
+        }
+
+        let slug = branch_slug(branch);
+        for (name, var) in &self.config.vars {
+            let Some(template) = &var.template else {
+                continue;
+            };
+            let mut value = template
+                .replace("{project}", self.project)
+                .replace("{branch}", branch)
+                .replace("{slug}", &slug);
+            for (other, other_value) in vars.clone() {
+                value = value.replace(&format!("{{{}}}", other), &other_value);
+            }
+            vars.insert(name.clone(), value);
+        }
+
+        Ok(vars)
+    }
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+// 其他程式正在使用的 port 不分配
+fn is_port_free(port: u16) -> bool {
+    TcpListener::bind(("127.0.0.1", port)).is_ok()
+}
+
+// 適合作為資料庫名稱等識別字：小寫英數字以 '_' 連接
+fn branch_slug(branch: &str) -> String {
+    branch.to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+fn render_env_file(vars: &EnvVars) -> String {
+    let mut content = String::from("# Generated by wt for this worktree; run 'wt env' to regenerate\n");
+    for (name, value) in vars {
+        let plain = value.chars().all(|c| c.is_ascii_alphanumeric() || "_-./:@".contains(c));
+        if plain {
+            content.push_str(&format!("{}={}\n", name, value));
+        } else {
+            content.push_str(&format!("{}=\"{}\"\n", name, value.replace('\\', "\\\\").replace('"', "\\\"")));
+        }
+    }
+    content
+}
+
+// 給 eval 使用的 export 敘述，值以單引號包住
+pub fn shell_exports(vars: &EnvVars) -> String {
+    vars.iter()
+        .map(|(name, value)| format!("export {}='{}'", name, value.replace('\'', "'\\''")))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> EnvVars {
+        pairs.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn exports_sorted_variables() {
+        let exports = shell_exports(&vars(&[("PORT", "3000"), ("API_URL", "http://localhost:3000")]));
+        assert_eq!(exports, "export API_URL='http://localhost:3000'\nexport PORT='3000'");
+    }
+
+    #[test]
+    fn quotes_values_for_the_shell() {
+        let exports = shell_exports(&vars(&[("NAME", "it's $HOME; `id`")]));
+        assert_eq!(exports, "export NAME='it'\\''s $HOME; `id`'");
+    }
+
+    #[test]
+    fn exports_nothing_without_variables() {
+        assert_eq!(shell_exports(&EnvVars::new()), "");
+    }
+
+    #[test]
+    fn slugs_branches_as_identifiers() {
+        assert_eq!(branch_slug("feat/ABC-123-Login"), "feat_abc_123_login");
+        assert_eq!(branch_slug("--x//y--"), "x_y");
+    }
+}
\ No newline at end of file
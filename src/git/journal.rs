@@ -0,0 +1,245 @@
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use crate::git::repository::Repository;
+use crate::git::worktree::WorktreeInfo;
+use crate::ui::display::Display;
+use crate::utils::dry_run;
+use crate::utils::logger;
+use crate::utils::time::now_timestamp;
+
+// 放在 git common dir 中，所有 worktree 共用同一份紀錄
+pub const JOURNAL_FILE_NAME: &str = "wt-journal.jsonl";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Operation {
+    Add,
+    Remove,
+    Move,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub id: u64,
+    pub timestamp: i64,
+    pub operation: Operation,
+    pub path: PathBuf,
+    // 移動前的路徑，只有 move 會記錄
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub moved_from: Option<PathBuf>,
+    // 分離 HEAD 的 worktree 沒有分支
+    pub branch: Option<String>,
+    // 操作當下的分支 tip（或分離 HEAD 的 commit）
+    pub commit: String,
+    // 新增時一併建立了本地分支，復原時可以刪除
+    #[serde(default)]
+    pub created_branch: bool,
+    // 強制移除前保存未提交變更的快照名稱
+    #[serde(default)]
+    pub snapshot: Option<String>,
+    #[serde(default)]
+    pub undone: bool,
+}
+
+impl JournalEntry {
+    pub fn short_commit(&self) -> &str {
+        &self.commit[..self.commit.len().min(7)]
+    }
+
+    pub fn name(&self) -> String {
+        match &self.branch {
+            Some(branch) => branch.clone(),
+            None => format!("detached@{}", self.short_commit()),
+        }
+    }
+}
+
+pub struct Journal {
+    path: PathBuf,
+}
+
+impl Journal {
+    pub fn open(repo: &Repository) -> Self {
+        Self { path: repo.inner.commondir().join(JOURNAL_FILE_NAME) }
+    }
+
+    pub fn entries(&self) -> Result<Vec<JournalEntry>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = fs::read_to_string(&self.path)?;
+        let mut entries = Vec::new();
+        for line in content.lines().filter(|line| !line.trim().is_empty()) {
+            // 無法解析的行（例如寫到一半中斷）直接略過
+            match serde_json::from_str(line) {
+                Ok(entry) => entries.push(entry),
+                Err(e) => logger::debug(&format!("skipping journal line: {}", e)),
+            }
+        }
+        Ok(entries)
+    }
+
+    // 乾跑時不記錄；回傳新紀錄的編號
+    pub fn record(&self, operation: Operation, worktree: &WorktreeInfo, created_branch: bool, snapshot: Option<&str>) -> Result<Option<u64>> {
+        self.append(operation, worktree, None, created_branch, snapshot)
+    }
+
+    // worktree 從 from 移到目前的路徑
+    pub fn record_move(&self, from: &Path, worktree: &WorktreeInfo) -> Result<Option<u64>> {
+        self.append(Operation::Move, worktree, Some(from), false, None)
+    }
+
+    fn append(&self, operation: Operation, worktree: &WorktreeInfo, moved_from: Option<&Path>, created_branch: bool, snapshot: Option<&str>) -> Result<Option<u64>> {
+        if dry_run::is_enabled() {
+            return Ok(None);
+        }
+
+        let id = self.entries()?.iter().map(|entry| entry.id).max().unwrap_or(0) + 1;
+        let entry = JournalEntry {
+            id,
+            timestamp: now_timestamp(),
+            operation,
+            path: worktree.path.clone(),
+            moved_from: moved_from.map(Path::to_path_buf),
+            branch: (!worktree.is_detached && !worktree.branch.is_empty()).then(|| worktree.branch.clone()),
+            commit: worktree.commit.clone(),
+            created_branch,
+            snapshot: snapshot.map(|name| name.to_string()),
+            undone: false,
+        };
+
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+        logger::debug(&format!("journal #{}: {:?} {}", id, operation, worktree.path.display()));
+        Ok(Some(id))
+    }
+
+    pub fn find(&self, id: u64) -> Result<JournalEntry> {
+        self.entries()?.into_iter()
+            .find(|entry| entry.id == id)
+            .ok_or_else(|| anyhow!("No journal entry #{}", id))
+    }
+
+    // 最近一筆尚未復原的操作
+    pub fn last_undoable(&self) -> Result<Option<JournalEntry>> {
+        Ok(self.entries()?.into_iter().rev().find(|entry| !entry.undone))
+    }
+
+    pub fn mark_undone(&self, id: u64) -> Result<()> {
+        let mut entries = self.entries()?;
+        for entry in entries.iter_mut().filter(|entry| entry.id == id) {
+            entry.undone = true;
+        }
+
+        let mut content = String::new();
+        for entry in &entries {
+            content.push_str(&serde_json::to_string(entry)?);
+            content.push('\n');
+        }
+        fs::write(&self.path, content)?;
+        Ok(())
+    }
+}
+
+// 寫入操作紀錄失敗不影響已完成的操作，只提出警告
+pub fn record_operation(repo: &Repository, operation: Operation, worktree: &WorktreeInfo, created_branch: bool, snapshot: Option<&str>) {
+    if let Err(e) = Journal::open(repo).record(operation, worktree, created_branch, snapshot) {
+        Display::show_warning(&format!("Could not record the operation for 'wt undo': {}", e));
+    }
+}
+
+pub fn record_move(repo: &Repository, from: &Path, worktree: &WorktreeInfo) {
+    if let Err(e) = Journal::open(repo).record_move(from, worktree) {
+        Display::show_warning(&format!("Could not record the operation for 'wt undo': {}", e));
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_journal(name: &str) -> Journal {
+        let dir = std::env::temp_dir().join(format!("wt-test-journal-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        Journal { path: dir.join(JOURNAL_FILE_NAME) }
+    }
+
+    fn worktree(branch: &str, is_detached: bool) -> WorktreeInfo {
+        WorktreeInfo {
+            path: PathBuf::from(format!("/tmp/app-{}", branch)),
+            branch: branch.to_string(),
+            commit: "0123456789abcdef0123456789abcdef01234567".to_string(),
+            is_current: false,
+            is_detached,
+            is_bare: false,
+            describe: None,
+        }
+    }
+
+    #[test]
+    fn records_and_reads_back_entries() {
+        let journal = temp_journal("round-trip");
+        assert!(journal.entries().unwrap().is_empty());
+
+        assert_eq!(journal.record(Operation::Add, &worktree("feature", false), true, None).unwrap(), Some(1));
+        assert_eq!(journal.record(Operation::Remove, &worktree("", true), false, Some("feature/1700000000")).unwrap(), Some(2));
+
+        let entries = journal.entries().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].id, 1);
+        assert_eq!(entries[0].operation, Operation::Add);
+        assert_eq!(entries[0].path, PathBuf::from("/tmp/app-feature"));
+        assert_eq!(entries[0].branch.as_deref(), Some("feature"));
+        assert_eq!(entries[0].commit, "0123456789abcdef0123456789abcdef01234567");
+        assert!(entries[0].created_branch);
+        assert!(!entries[0].undone);
+        assert_eq!(entries[0].snapshot, None);
+
+        // 分離 HEAD 的 worktree 以 commit 命名
+        assert_eq!(entries[1].operation, Operation::Remove);
+        assert_eq!(entries[1].branch, None);
+        assert_eq!(entries[1].name(), "detached@0123456");
+        assert_eq!(entries[1].snapshot.as_deref(), Some("feature/1700000000"));
+
+        let _ = fs::remove_dir_all(journal.path.parent().unwrap());
+    }
+
+    #[test]
+    fn marks_entries_undone() {
+        let journal = temp_journal("undone");
+        journal.record(Operation::Add, &worktree("one", false), false, None).unwrap();
+        journal.record(Operation::Add, &worktree("two", false), false, None).unwrap();
+
+        journal.mark_undone(2).unwrap();
+        assert!(journal.find(2).unwrap().undone);
+        assert_eq!(journal.last_undoable().unwrap().unwrap().id, 1);
+
+        journal.mark_undone(1).unwrap();
+        assert!(journal.last_undoable().unwrap().is_none());
+        assert!(journal.find(3).is_err());
+
+        let _ = fs::remove_dir_all(journal.path.parent().unwrap());
+    }
+
+    #[test]
+    fn skips_unreadable_lines() {
+        let journal = temp_journal("unreadable");
+        journal.record(Operation::Add, &worktree("feature", false), false, None).unwrap();
+        let mut file = OpenOptions::new().append(true).open(&journal.path).unwrap();
+        write!(file, "{{\"id\": 2, \"operation\"").unwrap();
+
+        let entries = journal.entries().unwrap();
+        assert_eq!(entries.len(), 1);
+
+        // 舊紀錄缺少的欄位使用預設值
+        fs::write(&journal.path, "{\"id\":7,\"timestamp\":0,\"operation\":\"remove\",\"path\":\"/tmp/x\",\"branch\":\"x\",\"commit\":\"abc\"}\n").unwrap();
+        let entry = journal.find(7).unwrap();
+        assert!(!entry.created_branch && !entry.undone);
+
+        let _ = fs::remove_dir_all(journal.path.parent().unwrap());
+    }
+}
\ No newline at end of file
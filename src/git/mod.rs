@@ -1,3 +1,9 @@
 pub mod repository;
 pub mod branches;
-pub mod worktree;
\ No newline at end of file
+pub mod worktree;
+pub mod context;
+pub mod journal;
+pub mod snapshots;
+pub mod stack;
+pub mod environment;
+pub mod setup;
\ No newline at end of file
@@ -2,20 +2,48 @@ use anyhow::{Result, anyhow};
 use git2::Repository as Git2Repository;
 use std::env;
 use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+use crate::config::Config;
+use crate::utils::logger;
+
+// hub 中存放 git 資料的目錄，hub 目錄本身以 .git 檔案指向它
+pub const HUB_GIT_DIR: &str = ".bare";
+
+// 由 --repo 或 WT_REPO 指定的探索起點
+static DISCOVERY_ROOT: OnceLock<PathBuf> = OnceLock::new();
 
 pub struct Repository {
     pub inner: Git2Repository,
 }
 
 impl Repository {
+    pub fn set_discovery_root(path: PathBuf) {
+        let _ = DISCOVERY_ROOT.set(path);
+    }
+
+    fn discovery_root() -> Result<PathBuf> {
+        match DISCOVERY_ROOT.get() {
+            Some(path) => Ok(path.clone()),
+            None => Ok(env::current_dir()?),
+        }
+    }
+
     pub fn open_current() -> Result<Self> {
-        let current_dir = env::current_dir()?;
-        let repo = Git2Repository::discover(&current_dir)
-            .map_err(|_| anyhow!("Not in a Git repository"))?;
+        let root = Self::discovery_root()?;
+        let repo = logger::git2(&format!("discover {}", root.display()), || Git2Repository::discover(&root))
+            .map_err(|_| anyhow!("Not in a Git repository: {}", root.display()))?;
         
         Ok(Repository { inner: repo })
     }
 
+    pub fn open(path: &Path) -> Result<Self> {
+        let repo = logger::git2(&format!("open {}", path.display()), || Git2Repository::open(path))
+            .map_err(|e| anyhow!("Failed to open repository at {}: {}", path.display(), e.message()))?;
+
+        Ok(Repository { inner: repo })
+    }
+
     pub fn get_project_name(&self) -> Result<String> {
         // 嘗試從 remote URL 提取專案名稱
         if let Ok(remote) = self.inner.find_remote("origin") {
@@ -26,31 +54,148 @@ impl Repository {
             }
         }
 
-        // 如果沒有 remote，則使用目錄名稱
-        let workdir = self.inner.workdir()
-            .ok_or_else(|| anyhow!("Repository has no working directory"))?;
+        // 如果沒有 remote，則使用目錄名稱（bare hub 會移除 .git 後綴）
+        let root = self.main_root()?;
         
-        let dir_name = workdir.file_name()
+        let dir_name = root.file_name()
             .and_then(|n| n.to_str())
             .ok_or_else(|| anyhow!("Invalid directory name"))?;
         
-        Ok(dir_name.to_string())
+        Ok(dir_name.trim_end_matches(".git").to_string())
+    }
+
+    // 共用的 git 目錄是否為 bare repository（包含從 hub 內的 worktree 執行的情況）
+    pub fn is_bare_hub(&self) -> bool {
+        if self.inner.is_bare() {
+            return true;
+        }
+        Git2Repository::open(self.inner.commondir())
+            .map(|common| common.is_bare())
+            .unwrap_or(false)
+    }
+
+    // project/.bare 搭配 project/.git 檔案的 hub：worktree 放在 hub 目錄中，與 git 資料分開
+    pub fn has_hub_directory(&self) -> bool {
+        self.is_bare_hub() && self.inner.commondir().file_name().is_some_and(|name| name == HUB_GIT_DIR)
+    }
+
+    // 主倉庫的根目錄：一般倉庫為主 worktree，bare hub 為 hub 目錄（沒有 hub 目錄時為 bare repository 本身）
+    pub fn main_root(&self) -> Result<PathBuf> {
+        // 移除結尾的分隔符號，讓顯示的路徑一致
+        let common_dir: PathBuf = self.inner.commondir().components().collect();
+        let common_dir = common_dir.as_path();
+
+        if self.has_hub_directory() {
+            if let Some(parent) = common_dir.parent() {
+                return Ok(parent.to_path_buf());
+            }
+        }
+        if self.is_bare_hub() {
+            return Ok(common_dir.to_path_buf());
+        }
+
+        common_dir.parent()
+            .map(|p| p.to_path_buf())
+            .ok_or_else(|| anyhow!("Cannot determine repository root"))
+    }
+
+    pub fn load_config(&self) -> Result<Config> {
+        // 設定檔位於主 worktree（或 bare hub）的根目錄
+        Config::load(&self.main_root()?)
+    }
+
+    // git 資料放在 dir/.bare，dir/.git 檔案指向它，worktree 才能與 git 資料並列在 dir 中
+    pub fn clone_bare_hub(url: &str, dir: &Path) -> Result<()> {
+        let git_dir = dir.join(HUB_GIT_DIR);
+        run_git(None, &["clone", "--bare", url, &git_dir.to_string_lossy()])?;
+        std::fs::write(dir.join(".git"), format!("gitdir: ./{}\n", HUB_GIT_DIR))?;
+
+        // bare clone 預設不會建立 remote-tracking 分支，補上 fetch 設定
+        fetch_all_branches(&git_dir)
+    }
+
+    pub fn clone_standard(url: &str, dir: &Path) -> Result<()> {
+        run_git(None, &["clone", url, &dir.to_string_lossy()])?;
+
+        // 確保所有分支都會被 fetch（例如來源設定了 single-branch）
+        fetch_all_branches(dir)
+    }
+
+    // 不複製倉庫，透過 ls-remote 查詢遠端的預設分支
+    pub fn remote_default_branch(url: &str) -> Result<String> {
+        let output = logger::output(Command::new("git").args(["ls-remote", "--symref", url, "HEAD"]))?;
+        if !output.status.success() {
+            return Err(anyhow!("git ls-remote {} failed: {}", url,
+                String::from_utf8_lossy(&output.stderr).trim()));
+        }
+
+        String::from_utf8_lossy(&output.stdout).lines()
+            .find_map(|line| line.strip_prefix("ref: refs/heads/"))
+            .and_then(|rest| rest.split_whitespace().next())
+            .map(|branch| branch.to_string())
+            .ok_or_else(|| anyhow!("Cannot determine the default branch of '{}'", url))
+    }
+
+    // 從另一個倉庫取得 reference 並存為 target_ref，回傳取得的 commit
+    pub fn fetch_ref(&self, source: &Path, reference: &str, target_ref: &str) -> Result<git2::Oid> {
+        let refspec = format!("+{}:{}", reference, target_ref);
+        run_git(Some(self.inner.commondir()), &["fetch", "--no-tags", &source.to_string_lossy(), &refspec])?;
+
+        let oid = logger::git2(&format!("resolve {}", target_ref), || self.inner.refname_to_id(target_ref))?;
+        Ok(oid)
+    }
+
+    // 將檔案加入 .git/info/exclude，避免出現在 git status
+    pub fn exclude_file(&self, name: &str) -> Result<()> {
+        let info_dir = self.inner.commondir().join("info");
+        std::fs::create_dir_all(&info_dir)?;
+
+        let exclude_path = info_dir.join("exclude");
+        let existing = std::fs::read_to_string(&exclude_path).unwrap_or_default();
+        if existing.lines().any(|line| line.trim() == name) {
+            return Ok(());
+        }
+
+        let separator = if existing.is_empty() || existing.ends_with('\n') { "" } else { "\n" };
+        std::fs::write(&exclude_path, format!("{}{}{}\n", existing, separator, name))?;
+        Ok(())
     }
 
     pub fn is_git_repository() -> bool {
-        match env::current_dir() {
-            Ok(current_dir) => {
-                Git2Repository::discover(&current_dir).is_ok()
+        match Self::discovery_root() {
+            Ok(root) => {
+                Git2Repository::discover(&root).is_ok()
             }
             Err(_) => false,
         }
     }
 }
 
-fn extract_project_name_from_url(url: &str) -> Option<String> {
+fn fetch_all_branches(dir: &Path) -> Result<()> {
+    run_git(Some(dir), &["config", "remote.origin.fetch", "+refs/heads/*:refs/remotes/origin/*"])?;
+    run_git(Some(dir), &["fetch", "origin"])
+}
+
+fn run_git(cwd: Option<&Path>, args: &[&str]) -> Result<()> {
+    let mut cmd = Command::new("git");
+    cmd.args(args);
+    if let Some(dir) = cwd {
+        cmd.current_dir(dir);
+    }
+
+    let output = logger::output(&mut cmd)?;
+    if !output.status.success() {
+        return Err(anyhow!("git {} failed: {}", args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()));
+    }
+
+    Ok(())
+}
+
+pub fn extract_project_name_from_url(url: &str) -> Option<String> {
     // 處理 HTTPS URL: https://github.com/user/repo.git
     if url.starts_with("https://") {
-        if let Some(last_part) = url.split('/').last() {
+        if let Some(last_part) = url.split('/').next_back() {
             let name = last_part.trim_end_matches(".git");
             return Some(name.to_string());
         }
@@ -60,12 +205,17 @@ fn extract_project_name_from_url(url: &str) -> Option<String> {
     if url.starts_with("git@") {
         if let Some(colon_pos) = url.find(':') {
             let after_colon = &url[colon_pos + 1..];
-            if let Some(last_part) = after_colon.split('/').last() {
+            if let Some(last_part) = after_colon.split('/').next_back() {
                 let name = last_part.trim_end_matches(".git");
                 return Some(name.to_string());
             }
         }
     }
     
-    None
+    // 其他格式（file://、ssh://、本地路徑）取最後一段
+    let name = url.trim_end_matches('/')
+        .rsplit(['/', ':', '\\'])
+        .next()?
+        .trim_end_matches(".git");
+    (!name.is_empty()).then(|| name.to_string())
 }
\ No newline at end of file
@@ -0,0 +1,191 @@
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use crate::config::SetupConfig;
+use crate::git::repository::Repository;
+use crate::ui::display::Display;
+use crate::utils::dry_run::{self, PlannedAction};
+use crate::utils::logger;
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SetupReport {
+    // 已初始化的 submodule 路徑
+    pub submodules: Vec<String>,
+    // "pulled"、"missing"（未安裝 git-lfs）或 "failed"；沒有使用 LFS 時為 None
+    pub lfs: Option<&'static str>,
+}
+
+struct Submodule {
+    name: String,
+    path: String,
+}
+
+// 建立 worktree 後初始化 submodule 並取出 LFS 檔案；失敗只提出警告，worktree 本身已建立完成
+// target 是 worktree 取出的分支或 commit
+pub fn prepare_worktree(repo: &Repository, config: &SetupConfig, path: &Path, target: &str) -> SetupReport {
+    let mut report = SetupReport::default();
+
+    // 乾跑時 worktree 尚未存在，以要取出的 commit 根目錄中的檔案判斷
+    let (has_submodules, uses_lfs) = if dry_run::is_enabled() {
+        detect_in_commit(repo, target)
+    } else {
+        (path.join(".gitmodules").is_file(), uses_lfs(path))
+    };
+
+    if config.submodules && has_submodules {
+        if dry_run::is_enabled() {
+            dry_run::record(PlannedAction::UpdateSubmodules { path: path.to_path_buf() });
+        } else {
+            report.submodules = update_submodules(repo, path);
+        }
+    }
+
+    if config.lfs && uses_lfs {
+        report.lfs = Some(pull_lfs(path));
+    }
+
+    report
+}
+
+// 已建立的 worktree 中會執行的步驟，供確認提示列出
+pub fn pending_steps(config: &SetupConfig, path: &Path) -> Vec<String> {
+    let mut steps = Vec::new();
+    if config.submodules && path.join(".gitmodules").is_file() {
+        steps.push("initialize the submodules listed in .gitmodules".to_string());
+    }
+    if config.lfs && uses_lfs(path) {
+        steps.push("download Git LFS files with 'git lfs pull'".to_string());
+    }
+    steps
+}
+
+fn update_submodules(repo: &Repository, path: &Path) -> Vec<String> {
+    // sparse checkout 排除的 submodule 不初始化
+    let submodules: Vec<Submodule> = list_submodules(path).into_iter()
+        .filter(|submodule| !is_skipped(path, &submodule.path))
+        .collect();
+
+    let mut initialized = Vec::new();
+    for (index, submodule) in submodules.iter().enumerate() {
+        Display::show_info(&format!("[{}/{}] Initializing submodule '{}'...", index + 1, submodules.len(), submodule.path));
+
+        // 主 checkout 已有這個 submodule 時借用它的物件，不必重新下載
+        let reference: PathBuf = repo.inner.commondir().join("modules").join(&submodule.name);
+        let mut args = vec!["submodule", "update", "--init"];
+        let reference = reference.to_string_lossy().to_string();
+        if Path::new(&reference).is_dir() {
+            args.extend(["--reference", reference.as_str()]);
+        }
+        args.extend(["--", submodule.path.as_str()]);
+
+        if run_visible(path, &args) {
+            initialized.push(submodule.path.clone());
+        } else {
+            Display::show_warning(&format!(
+                "Failed to initialize submodule '{}'; run 'git submodule update --init --recursive' in {}",
+                submodule.path, path.display()));
+        }
+    }
+
+    // 巢狀的 submodule 交給 git 遞迴處理
+    let nested = initialized.iter().any(|submodule| path.join(submodule).join(".gitmodules").is_file());
+    if nested {
+        Display::show_info("Initializing nested submodules...");
+        let mut args = vec!["submodule", "update", "--init", "--recursive", "--"];
+        args.extend(initialized.iter().map(|submodule| submodule.as_str()));
+        if !run_visible(path, &args) {
+            Display::show_warning(&format!(
+                "Failed to initialize nested submodules; run 'git submodule update --init --recursive' in {}",
+                path.display()));
+        }
+    }
+
+    initialized
+}
+
+fn pull_lfs(path: &Path) -> &'static str {
+    let available = logger::output(Command::new("git").args(["lfs", "version"]))
+        .map(|output| output.status.success())
+        .unwrap_or(false);
+    if !available {
+        Display::show_warning(&format!(
+            "This repository uses Git LFS but git-lfs is not installed, so LFS files in {} are pointer files. \
+            Install git-lfs (https://git-lfs.com), then run 'git lfs pull' there.",
+            path.display()));
+        return "missing";
+    }
+
+    if dry_run::is_enabled() {
+        dry_run::record(PlannedAction::PullLfs { path: path.to_path_buf() });
+        return "pulled";
+    }
+
+    Display::show_info("Downloading Git LFS files...");
+    if run_visible(path, &["lfs", "pull"]) {
+        "pulled"
+    } else {
+        Display::show_warning(&format!("'git lfs pull' failed; run it again in {}", path.display()));
+        "failed"
+    }
+}
+
+fn list_submodules(path: &Path) -> Vec<Submodule> {
+    let Ok(output) = logger::output(Command::new("git")
+        .args(["config", "--file", ".gitmodules", "--get-regexp", r"^submodule\..*\.path$"])
+        .current_dir(path)) else {
+        return Vec::new();
+    };
+
+    String::from_utf8_lossy(&output.stdout).lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(' ')?;
+            let name = key.strip_prefix("submodule.")?.strip_suffix(".path")?;
+            Some(Submodule { name: name.to_string(), path: value.to_string() })
+        })
+        .collect()
+}
+
+// ls-files -t 以 'S' 標示 skip-worktree 的項目
+fn is_skipped(path: &Path, submodule: &str) -> bool {
+    logger::output(Command::new("git")
+        .args(["ls-files", "-t", "--", submodule])
+        .current_dir(path))
+        .map(|output| String::from_utf8_lossy(&output.stdout).starts_with("S "))
+        .unwrap_or(false)
+}
+
+// 任何一個 .gitattributes 中以 filter=lfs 追蹤檔案
+fn uses_lfs(path: &Path) -> bool {
+    let Ok(output) = logger::output(Command::new("git")
+        .args(["ls-files", "-z", "--", ".gitattributes", ":(glob)**/.gitattributes"])
+        .current_dir(path)) else {
+        return false;
+    };
+
+    String::from_utf8_lossy(&output.stdout).split('\0')
+        .filter(|file| !file.is_empty())
+        .any(|file| fs::read_to_string(path.join(file)).is_ok_and(|content| content.contains("filter=lfs")))
+}
+
+fn detect_in_commit(repo: &Repository, target: &str) -> (bool, bool) {
+    let Ok(tree) = repo.inner.revparse_single(target).and_then(|object| object.peel_to_tree()) else {
+        return (false, false);
+    };
+    let has_submodules = tree.get_name(".gitmodules").is_some();
+    let uses_lfs = tree.get_name(".gitattributes")
+        .and_then(|entry| entry.to_object(&repo.inner).ok())
+        .and_then(|object| object.into_blob().ok())
+        .is_some_and(|blob| String::from_utf8_lossy(blob.content()).contains("filter=lfs"));
+    (has_submodules, uses_lfs)
+}
+
+// git 的進度輸出直接顯示給使用者；JSON 模式下 stdout 改到 stderr
+fn run_visible(path: &Path, args: &[&str]) -> bool {
+    let mut cmd = Command::new("git");
+    cmd.args(args).current_dir(path);
+    if !Display::is_human() {
+        cmd.stdout(Stdio::from(std::io::stderr()));
+    }
+    logger::status(&mut cmd).map(|status| status.success()).unwrap_or(false)
+}
\ No newline at end of file
@@ -0,0 +1,210 @@
+use anyhow::{Result, anyhow};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use crate::git::repository::Repository;
+use crate::utils::dry_run::{self, PlannedAction};
+use crate::utils::logger;
+use crate::utils::time::now_timestamp;
+
+pub const SNAPSHOT_REF_PREFIX: &str = "refs/wt/snapshots/";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Snapshot {
+    // <branch>/<timestamp>，同一秒內的快照加上 -2、-3 等序號；在命令列中指定快照時使用
+    pub name: String,
+    pub reference: String,
+    pub branch: String,
+    pub timestamp: i64,
+    pub commit: String,
+}
+
+impl Snapshot {
+    pub fn short_commit(&self) -> &str {
+        &self.commit[..self.commit.len().min(7)]
+    }
+}
+
+pub struct SnapshotManager<'a> {
+    repo: &'a Repository,
+}
+
+impl<'a> SnapshotManager<'a> {
+    pub fn new(repo: &'a Repository) -> Self {
+        Self { repo }
+    }
+
+    pub fn list(&self) -> Result<Vec<Snapshot>> {
+        let references = logger::git2("list snapshots", || {
+            self.repo.inner.references_glob(&format!("{}*", SNAPSHOT_REF_PREFIX))
+        })?;
+
+        let mut snapshots = Vec::new();
+        for reference in references {
+            let reference = reference?;
+            let (Some(full_name), Some(oid)) = (reference.name(), reference.target()) else {
+                continue;
+            };
+            let name = &full_name[SNAPSHOT_REF_PREFIX.len()..];
+            let Some((branch, stamp)) = name.rsplit_once('/') else {
+                continue;
+            };
+            let (timestamp, sequence) = stamp.split_once('-').unwrap_or((stamp, "1"));
+
+            let snapshot = Snapshot {
+                name: name.to_string(),
+                reference: full_name.to_string(),
+                branch: branch.to_string(),
+                timestamp: timestamp.parse().unwrap_or(0),
+                commit: oid.to_string(),
+            };
+            snapshots.push((snapshot, sequence.parse::<u32>().unwrap_or(1)));
+        }
+
+        snapshots.sort_by_key(|(snapshot, sequence)| (snapshot.timestamp, *sequence));
+        Ok(snapshots.into_iter().map(|(snapshot, _)| snapshot).collect())
+    }
+
+    pub fn find(&self, name: &str) -> Result<Snapshot> {
+        let name = name.strip_prefix(SNAPSHOT_REF_PREFIX).unwrap_or(name);
+        self.list()?.into_iter()
+            .find(|snapshot| snapshot.name == name)
+            .ok_or_else(|| anyhow!("No snapshot named '{}'", name))
+    }
+
+    // 將 worktree 中已暫存、未暫存與未追蹤的變更存成與 git stash 相同格式的 commit，
+    // 因此之後可以用 git stash apply 套用到任何 worktree
+    pub fn create(&self, worktree_path: &Path, branch: &str) -> Result<Snapshot> {
+        let timestamp = now_timestamp();
+
+        if dry_run::is_enabled() {
+            let sequence = (1..).find(|&sequence| {
+                self.repo.inner.find_reference(&snapshot_reference(branch, timestamp, sequence)).is_err()
+            }).unwrap_or(1);
+            let name = snapshot_name(branch, timestamp, sequence);
+            let reference = snapshot_reference(branch, timestamp, sequence);
+            dry_run::record(PlannedAction::CreateSnapshot {
+                worktree: worktree_path.to_path_buf(),
+                reference: reference.clone(),
+            });
+            return Ok(Snapshot { name, reference, branch: branch.to_string(), timestamp, commit: String::new() });
+        }
+
+        let head = git(worktree_path, &["rev-parse", "HEAD"], None)?;
+        let short_head = &head[..head.len().min(7)];
+
+        // 索引的內容
+        let index_tree = git(worktree_path, &["write-tree"], None)?;
+        let index_commit = git(worktree_path, &["commit-tree", &index_tree, "-p", &head,
+            "-m", &format!("index on {}: {}", branch, short_head)], None)?;
+
+        let temp_index = TempIndex::new("snapshot");
+
+        // 未追蹤的檔案（不含 .gitignore 排除的檔案）放在獨立的 commit
+        let untracked = git(worktree_path, &["ls-files", "--others", "--exclude-standard", "-z"], None)?;
+        let untracked_commit = if untracked.is_empty() {
+            None
+        } else {
+            let pathspec = temp_index.path.with_extension("pathspec");
+            fs::write(&pathspec, &untracked)?;
+            let added = git(worktree_path, &["add", "--pathspec-from-file", &pathspec.to_string_lossy(),
+                "--pathspec-file-nul"], Some(&temp_index.path));
+            fs::remove_file(&pathspec).ok();
+            added?;
+
+            let tree = git(worktree_path, &["write-tree"], Some(&temp_index.path))?;
+            fs::remove_file(&temp_index.path).ok();
+            Some(git(worktree_path, &["commit-tree", &tree,
+                "-m", &format!("untracked files on {}: {}", branch, short_head)], None)?)
+        };
+
+        // 工作目錄中已追蹤檔案的內容
+        git(worktree_path, &["read-tree", &index_tree], Some(&temp_index.path))?;
+        git(worktree_path, &["add", "--update"], Some(&temp_index.path))?;
+        let worktree_tree = git(worktree_path, &["write-tree"], Some(&temp_index.path))?;
+
+        let message = format!("WIP on {}: {}", branch, short_head);
+        let mut args = vec!["commit-tree", &worktree_tree, "-p", &head, "-p", &index_commit];
+        if let Some(commit) = &untracked_commit {
+            args.extend(["-p", commit]);
+        }
+        args.extend(["-m", &message]);
+        let commit = git(worktree_path, &args, None)?;
+
+        // 不覆寫既有的快照：同一秒內已有快照時改用下一個序號
+        let oid = git2::Oid::from_str(&commit)?;
+        let mut sequence = 1;
+        loop {
+            let reference = snapshot_reference(branch, timestamp, sequence);
+            match logger::git2("create snapshot ref", || self.repo.inner.reference(&reference, oid, false, "wt snapshot")) {
+                Ok(_) => break,
+                Err(e) if e.code() == git2::ErrorCode::Exists => sequence += 1,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        let name = snapshot_name(branch, timestamp, sequence);
+        let reference = snapshot_reference(branch, timestamp, sequence);
+
+        Ok(Snapshot { name, reference, branch: branch.to_string(), timestamp, commit })
+    }
+
+    pub fn apply(&self, snapshot: &Snapshot, worktree_path: &Path) -> Result<()> {
+        if dry_run::is_enabled() {
+            dry_run::record(PlannedAction::ApplySnapshot {
+                reference: snapshot.reference.clone(),
+                worktree: worktree_path.to_path_buf(),
+            });
+            return Ok(());
+        }
+
+        git(worktree_path, &["stash", "apply", "--index", &snapshot.commit], None)
+            .map(|_| ())
+            .map_err(|e| anyhow!("Failed to apply snapshot '{}': {}", snapshot.name, e))
+    }
+}
+
+fn snapshot_name(branch: &str, timestamp: i64, sequence: u32) -> String {
+    match sequence {
+        1 => format!("{}/{}", branch, timestamp),
+        _ => format!("{}/{}-{}", branch, timestamp, sequence),
+    }
+}
+
+fn snapshot_reference(branch: &str, timestamp: i64, sequence: u32) -> String {
+    format!("{}{}", SNAPSHOT_REF_PREFIX, snapshot_name(branch, timestamp, sequence))
+}
+
+// 暫存用的索引檔，結束時自動刪除
+struct TempIndex {
+    path: PathBuf,
+}
+
+impl TempIndex {
+    fn new(purpose: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("wt-{}-{}.index", purpose, std::process::id()));
+        Self { path }
+    }
+}
+
+impl Drop for TempIndex {
+    fn drop(&mut self) {
+        fs::remove_file(&self.path).ok();
+    }
+}
+
+fn git(cwd: &Path, args: &[&str], index_file: Option<&Path>) -> Result<String> {
+    let mut cmd = Command::new("git");
+    cmd.args(args).current_dir(cwd);
+    if let Some(index_file) = index_file {
+        cmd.env("GIT_INDEX_FILE", index_file);
+    }
+
+    let output = logger::output(&mut cmd)?;
+    if !output.status.success() {
+        return Err(anyhow!("git {} failed: {}", args[0],
+            String::from_utf8_lossy(&output.stderr).trim()));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
\ No newline at end of file
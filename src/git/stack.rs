@@ -0,0 +1,148 @@
+use anyhow::Result;
+use serde::Serialize;
+use crate::git::repository::Repository;
+use crate::utils::dry_run::{self, PlannedAction};
+use crate::utils::logger;
+
+// 父分支記錄在 git config 的 branch.<name> 區段中，分支刪除時 git 會一併清除
+const PARENT_KEY: &str = "wt-parent";
+const BASE_KEY: &str = "wt-base";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StackLink {
+    pub branch: String,
+    pub parent: String,
+    // 上次建立或 restack 時父分支的 tip，rebase 時作為舊的起點
+    pub base: Option<String>,
+}
+
+pub struct StackManager<'a> {
+    repo: &'a Repository,
+}
+
+impl<'a> StackManager<'a> {
+    pub fn new(repo: &'a Repository) -> Self {
+        Self { repo }
+    }
+
+    pub fn links(&self) -> Result<Vec<StackLink>> {
+        let config = self.repo.inner.config()?.snapshot()?;
+        let mut links = Vec::new();
+
+        let mut entries = config.entries(Some(&format!(r"^branch\..*\.{}$", PARENT_KEY)))?;
+        while let Some(entry) = entries.next() {
+            let entry = entry?;
+            let (Some(name), Some(parent)) = (entry.name(), entry.value()) else {
+                continue;
+            };
+            let Some(branch) = name.strip_prefix("branch.").and_then(|n| n.strip_suffix(&format!(".{}", PARENT_KEY))) else {
+                continue;
+            };
+            links.push(StackLink {
+                branch: branch.to_string(),
+                parent: parent.to_string(),
+                base: config.get_string(&base_key(branch)).ok(),
+            });
+        }
+
+        links.sort_by(|a, b| a.branch.cmp(&b.branch));
+        Ok(links)
+    }
+
+    pub fn set_parent(&self, branch: &str, parent: &str, base: &str) -> Result<()> {
+        if dry_run::is_enabled() {
+            dry_run::record(PlannedAction::SetParent { branch: branch.to_string(), parent: parent.to_string() });
+            return Ok(());
+        }
+
+        logger::git2(&format!("set parent of {} to {}", branch, parent), || {
+            let mut config = self.repo.inner.config()?;
+            config.set_str(&parent_key(branch), parent)?;
+            config.set_str(&base_key(branch), base)
+        })?;
+        Ok(())
+    }
+
+    // restack 後記錄新的起點；只是中繼資料，dry-run 時不列入計畫
+    pub fn set_base(&self, branch: &str, base: &str) -> Result<()> {
+        if dry_run::is_enabled() {
+            return Ok(());
+        }
+
+        logger::git2(&format!("set base of {}", branch), || {
+            self.repo.inner.config()?.set_str(&base_key(branch), base)
+        })?;
+        Ok(())
+    }
+
+    // 依深度排序，父分支一定排在子分支之前
+    pub fn ordered_links(&self) -> Result<Vec<StackLink>> {
+        let links = self.links()?;
+        let depth = |link: &StackLink| {
+            let mut depth = 0;
+            let mut current = link.parent.as_str();
+            // 以連結數量為上限，避免設定錯誤造成的循環
+            while let Some(parent) = links.iter().find(|l| l.branch == current).filter(|_| depth < links.len()) {
+                depth += 1;
+                current = &parent.parent;
+            }
+            depth
+        };
+
+        let mut ordered = links.clone();
+        ordered.sort_by_key(|link| (depth(link), link.branch.clone()));
+        Ok(ordered)
+    }
+}
+
+fn parent_key(branch: &str) -> String {
+    format!("branch.{}.{}", branch, PARENT_KEY)
+}
+
+fn base_key(branch: &str) -> String {
+    format!("branch.{}.{}", branch, BASE_KEY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn temp_repo(name: &str) -> (std::path::PathBuf, Repository) {
+        let dir = std::env::temp_dir().join(format!("wt-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let inner = git2::Repository::init(&dir).unwrap();
+        (dir, Repository { inner })
+    }
+
+    fn branches(links: &[StackLink]) -> Vec<&str> {
+        links.iter().map(|link| link.branch.as_str()).collect()
+    }
+
+    #[test]
+    fn orders_parents_before_children() {
+        let (dir, repo) = temp_repo("stack-order");
+        let stack = StackManager::new(&repo);
+        stack.set_parent("c", "b", "0002").unwrap();
+        stack.set_parent("b", "a", "0001").unwrap();
+        stack.set_parent("a", "main", "0000").unwrap();
+        stack.set_parent("z", "main", "0000").unwrap();
+
+        let ordered = stack.ordered_links().unwrap();
+        assert_eq!(branches(&ordered), ["a", "z", "b", "c"]);
+        assert_eq!(ordered[2].parent, "a");
+        assert_eq!(ordered[2].base.as_deref(), Some("0001"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn stops_at_cycles() {
+        let (dir, repo) = temp_repo("stack-cycle");
+        let stack = StackManager::new(&repo);
+        stack.set_parent("x", "y", "0000").unwrap();
+        stack.set_parent("y", "x", "0000").unwrap();
+
+        assert_eq!(branches(&stack.ordered_links().unwrap()), ["x", "y"]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
\ No newline at end of file
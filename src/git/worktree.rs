@@ -1,37 +1,80 @@
 use anyhow::{Result, anyhow};
-use std::path::PathBuf;
+use serde::Serialize;
+use crate::error::WtError;
+use crate::ui::colors::Theme;
+use crate::utils::dry_run::{self, PlannedAction};
+use crate::utils::logger;
+use crate::utils::paths::move_contents;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::fs;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct WorktreeInfo {
     pub path: PathBuf,
     pub branch: String,
     pub commit: String,
     pub is_current: bool,
+    pub is_detached: bool,
+    pub is_bare: bool,
+    pub describe: Option<String>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+impl WorktreeInfo {
+    pub fn short_commit(&self) -> &str {
+        &self.commit[..self.commit.len().min(7)]
+    }
+
+    // 在命令列中指定 worktree 時使用的名稱：分支名稱，分離 HEAD 時為目錄名稱
+    pub fn name(&self) -> String {
+        if self.is_detached || self.branch.is_empty() {
+            return self.path.file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+        }
+        self.branch.clone()
+    }
+
+    // 分離 HEAD 的 worktree 以短 SHA 與描述的 tag 顯示
+    pub fn display_name(&self) -> String {
+        if !self.is_detached {
+            return self.branch.clone();
+        }
+        match &self.describe {
+            Some(describe) => format!("detached@{} ({})", self.short_commit(), describe),
+            None => format!("detached@{}", self.short_commit()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum BranchMode {
     NewBranch,
     ExistingBranch,
+    Detached,
 }
 
 pub struct WorktreeManager {
     repo_path: PathBuf,
+    current_worktree: Option<PathBuf>,
 }
 
 impl WorktreeManager {
     pub fn new(repo_path: PathBuf) -> Self {
-        Self { repo_path }
+        Self { repo_path, current_worktree: None }
+    }
+
+    pub fn with_current_worktree(mut self, current_worktree: Option<PathBuf>) -> Self {
+        self.current_worktree = current_worktree;
+        self
     }
 
     pub fn list_worktrees(&self) -> Result<Vec<WorktreeInfo>> {
         // 使用 git worktree list --porcelain 來獲取 worktree 資訊
-        let output = Command::new("git")
-            .args(&["worktree", "list", "--porcelain"])
-            .current_dir(&self.repo_path)
-            .output()?;
+        let output = logger::output(Command::new("git")
+            .args(["worktree", "list", "--porcelain"])
+            .current_dir(&self.repo_path))?;
 
         if !output.status.success() {
             return Err(anyhow!("Failed to list worktrees: {}", 
@@ -43,19 +86,22 @@ impl WorktreeManager {
         let mut current_worktree: Option<WorktreeInfo> = None;
         
         for line in output_str.lines() {
-            if line.starts_with("worktree ") {
+            if let Some(path) = line.strip_prefix("worktree ") {
                 // 如果有之前的 worktree，先添加它
                 if let Some(wt) = current_worktree.take() {
                     worktrees.push(wt);
                 }
                 
                 // 開始新的 worktree
-                let path = PathBuf::from(&line[9..]);
+                let path = PathBuf::from(path);
                 current_worktree = Some(WorktreeInfo {
                     path,
                     branch: String::new(),
                     commit: String::new(),
                     is_current: false,
+                    is_detached: false,
+                    is_bare: false,
+                    describe: None,
                 });
             } else if line.starts_with("HEAD ") && current_worktree.is_some() {
                 if let Some(ref mut wt) = current_worktree {
@@ -69,10 +115,14 @@ impl WorktreeManager {
                         .unwrap_or(branch_ref)
                         .to_string();
                 }
+            } else if line == "detached" && current_worktree.is_some() {
+                if let Some(ref mut wt) = current_worktree {
+                    wt.is_detached = true;
+                }
             } else if line == "bare" && current_worktree.is_some() {
-                // 跳過 bare repository
+                // 標記 bare repository（hub 本身不是 worktree）
                 if let Some(ref mut wt) = current_worktree {
-                    wt.branch = "(bare)".to_string();
+                    wt.is_bare = true;
                 }
             }
         }
@@ -82,13 +132,108 @@ impl WorktreeManager {
             worktrees.push(wt);
         }
 
-        // 移除主倉庫 (通常是第一個)
-        worktrees.retain(|wt| wt.branch != "(bare)" && !wt.path.ends_with(".git"));
+        // 移除 bare repository 的項目
+        worktrees.retain(|wt| !wt.is_bare);
+
+        if let Some(current) = &self.current_worktree {
+            mark_current_worktree(&mut worktrees, current);
+        }
+
+        for wt in worktrees.iter_mut().filter(|wt| wt.is_detached) {
+            wt.describe = self.describe_commit(&wt.commit);
+        }
         
         Ok(worktrees)
     }
 
-    pub fn add_worktree(&self, path: &PathBuf, branch: &str, branch_mode: &BranchMode, base_branch: Option<&str>) -> Result<()> {
+    // 依分支名稱、目錄名稱或路徑尋找 worktree
+    pub fn find_worktree(&self, worktrees: &[WorktreeInfo], query: &str) -> Result<WorktreeInfo> {
+        let query_path = Path::new(query).canonicalize().ok();
+
+        worktrees.iter()
+            .find(|wt| wt.branch == query || wt.name() == query)
+            .or_else(|| worktrees.iter().find(|wt| {
+                query_path.is_some() && wt.path.canonicalize().ok() == query_path
+            }))
+            .or_else(|| worktrees.iter().find(|wt| {
+                wt.path.file_name().is_some_and(|name| name == query)
+            }))
+            .cloned()
+            .ok_or_else(|| anyhow!("No worktree matches '{}'", query))
+    }
+
+    pub fn add_worktree(&self, path: &Path, branch: &str, branch_mode: &BranchMode, base_branch: Option<&str>) -> Result<()> {
+        self.add(path, branch, branch_mode, base_branch, true)
+    }
+
+    // 只建立 worktree 與分支而不取出檔案，之後由呼叫者填入工作目錄的內容
+    pub fn add_worktree_without_checkout(&self, path: &Path, branch: &str, branch_mode: &BranchMode, base_branch: Option<&str>) -> Result<()> {
+        self.add(path, branch, branch_mode, base_branch, false)
+    }
+
+    // 以既有目錄中的檔案作為新 worktree 的工作目錄內容，未提交的變更會保留為未暫存的變更
+    pub fn add_worktree_from_directory(&self, source: &Path, path: &Path, branch: &str, branch_mode: &BranchMode, base_branch: Option<&str>) -> Result<()> {
+        if dry_run::is_enabled() {
+            plan_add_worktree(path, branch, branch_mode, base_branch);
+            dry_run::record(PlannedAction::MoveFiles { from: source.to_path_buf(), to: path.to_path_buf() });
+            return Ok(());
+        }
+
+        self.add_worktree_without_checkout(path, branch, branch_mode, base_branch)?;
+        move_contents(source, path)?;
+        self.reset_index(path)
+    }
+
+    // 以 cone 模式的 sparse checkout 建立 worktree，只取出指定的目錄（以及根目錄的檔案）
+    pub fn add_worktree_sparse(&self, path: &Path, branch: &str, branch_mode: &BranchMode, base_branch: Option<&str>, directories: &[String]) -> Result<()> {
+        if dry_run::is_enabled() {
+            plan_add_worktree(path, branch, branch_mode, base_branch);
+            dry_run::record(PlannedAction::SetSparse { path: path.to_path_buf(), directories: directories.to_vec() });
+            return Ok(());
+        }
+
+        self.add_worktree_without_checkout(path, branch, branch_mode, base_branch)?;
+        self.set_sparse(path, directories)?;
+        git_in(path, &["checkout"], "check out")
+    }
+
+    // 不是 sparse checkout 時為 None
+    pub fn sparse_directories(&self, path: &Path) -> Result<Option<Vec<String>>> {
+        let enabled = logger::output(Command::new("git")
+            .args(["config", "--get", "core.sparseCheckout"])
+            .current_dir(path))?;
+        if String::from_utf8_lossy(&enabled.stdout).trim() != "true" {
+            return Ok(None);
+        }
+
+        let output = logger::output(Command::new("git")
+            .args(["sparse-checkout", "list"])
+            .current_dir(path))?;
+        if !output.status.success() {
+            return Err(anyhow!("Failed to list sparse directories of {}: {}",
+                path.display(), String::from_utf8_lossy(&output.stderr)));
+        }
+
+        Ok(Some(String::from_utf8_lossy(&output.stdout).lines().map(|line| line.to_string()).collect()))
+    }
+
+    pub fn set_sparse(&self, path: &Path, directories: &[String]) -> Result<()> {
+        if dry_run::is_enabled() {
+            dry_run::record(PlannedAction::SetSparse { path: path.to_path_buf(), directories: directories.to_vec() });
+            return Ok(());
+        }
+
+        let mut args = vec!["sparse-checkout", "set", "--cone", "--"];
+        args.extend(directories.iter().map(|dir| dir.as_str()));
+        git_in(path, &args, "set up sparse checkout")
+    }
+
+    fn add(&self, path: &Path, branch: &str, branch_mode: &BranchMode, base_branch: Option<&str>, checkout: bool) -> Result<()> {
+        if dry_run::is_enabled() {
+            plan_add_worktree(path, branch, branch_mode, base_branch);
+            return Ok(());
+        }
+
         // 確保父目錄存在
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent)?;
@@ -97,6 +242,9 @@ impl WorktreeManager {
         // 根據分支模式構建命令
         let mut cmd = Command::new("git");
         cmd.arg("worktree").arg("add");
+        if !checkout {
+            cmd.arg("--no-checkout");
+        }
 
         match branch_mode {
             BranchMode::NewBranch => {
@@ -110,9 +258,13 @@ impl WorktreeManager {
                 // 使用現有分支: git worktree add <path> <existing-branch>
                 cmd.arg(path.to_string_lossy().as_ref()).arg(branch);
             }
+            BranchMode::Detached => {
+                // 分離 HEAD: git worktree add --detach <path> <ref>
+                cmd.arg("--detach").arg(path.to_string_lossy().as_ref()).arg(branch);
+            }
         }
 
-        let output = cmd.current_dir(&self.repo_path).output()?;
+        let output = logger::output(cmd.current_dir(&self.repo_path))?;
 
         if !output.status.success() {
             let error_message = String::from_utf8_lossy(&output.stderr);
@@ -140,14 +292,16 @@ impl WorktreeManager {
                     String::new()
                 };
                 
-                return Err(anyhow!(
-                    "❌ Branch '{}' is already in use by another worktree{}\n\n\
-                    💡 Solutions:\n\
-                    • Run 'wt add' again and choose \"Create new branch\" to make a new branch based on '{}'\n\
-                    • Use 'git worktree list' to see all active worktrees\n\
-                    • Remove the conflicting worktree with 'git worktree remove <path>' if no longer needed",
-                    branch_info, location_info, branch_info
-                ));
+                let theme = Theme::current();
+                let bullet = theme.bullet();
+                return Err(WtError::BranchInUse(format!(
+                    "Branch '{}' is already in use by another worktree{}\n\n\
+                    {} Solutions:\n\
+                    {} Run 'wt add' again and choose \"Create new branch\" to make a new branch based on '{}'\n\
+                    {} Use 'git worktree list' to see all active worktrees\n\
+                    {} Remove the conflicting worktree with 'git worktree remove <path>' if no longer needed",
+                    branch_info, location_info, theme.hint(), bullet, branch_info, bullet, bullet
+                )).into());
             }
             
             return Err(anyhow!("Failed to add worktree: {}", error_message));
@@ -156,12 +310,173 @@ impl WorktreeManager {
         Ok(())
     }
 
-    pub fn remove_worktree(&self, path: &PathBuf) -> Result<()> {
-        // 使用 git worktree remove 命令
-        let output = Command::new("git")
-            .args(&["worktree", "remove", &path.to_string_lossy()])
-            .current_dir(&self.repo_path)
-            .output()?;
+    pub fn is_dirty(&self, path: &Path) -> Result<bool> {
+        let output = logger::output(Command::new("git")
+            .args(["status", "--porcelain"])
+            .current_dir(path))?;
+
+        if !output.status.success() {
+            return Err(anyhow!("Failed to get status of {}: {}",
+                path.display(), String::from_utf8_lossy(&output.stderr)));
+        }
+
+        Ok(!output.stdout.is_empty())
+    }
+
+    // 將 worktree 目前的分支中 upstream 之後的 commit 移到 onto 上
+    pub fn rebase(&self, path: &Path, branch: &str, onto: &str, upstream: &str) -> Result<()> {
+        if dry_run::is_enabled() {
+            dry_run::record(PlannedAction::Rebase { branch: branch.to_string(), onto: onto.to_string() });
+            return Ok(());
+        }
+
+        let output = logger::output(Command::new("git")
+            .args(["rebase", "--onto", onto, upstream])
+            .current_dir(path))?;
+
+        if !output.status.success() {
+            return Err(anyhow!("Rebasing '{}' onto '{}' stopped: {}\n\
+                Resolve it in {} and run 'git rebase --continue' (or 'git rebase --abort'), then run 'wt restack' again",
+                branch, onto, String::from_utf8_lossy(&output.stderr).trim(), path.display()));
+        }
+
+        Ok(())
+    }
+
+    // 清除目錄已不存在的 worktree 管理資料
+    pub fn prune(&self) -> Result<()> {
+        if dry_run::is_enabled() {
+            dry_run::record(PlannedAction::PruneWorktrees);
+            return Ok(());
+        }
+
+        let output = logger::output(Command::new("git")
+            .args(["worktree", "prune"])
+            .current_dir(&self.repo_path))?;
+
+        if !output.status.success() {
+            return Err(anyhow!("Failed to prune worktrees: {}", String::from_utf8_lossy(&output.stderr)));
+        }
+
+        Ok(())
+    }
+
+    // 重新連結 worktree 與倉庫之間的路徑（任一方被移動後）
+    // 由 worktree 的 .git 檔案找回倉庫，用於被移動的 worktree
+    pub fn repair(&self, path: &Path) -> Result<()> {
+        self.run_repair(path, Some(path))
+    }
+
+    // 由倉庫登記的路徑重寫 worktree 的 .git 檔案；.git 檔案已無法指回倉庫時使用
+    pub fn repair_links(&self, path: &Path) -> Result<()> {
+        self.run_repair(path, None)
+    }
+
+    fn run_repair(&self, path: &Path, from_worktree: Option<&Path>) -> Result<()> {
+        if dry_run::is_enabled() {
+            dry_run::record(PlannedAction::RepairWorktree { path: path.to_path_buf() });
+            return Ok(());
+        }
+
+        let mut cmd = Command::new("git");
+        cmd.args(["worktree", "repair"]).current_dir(&self.repo_path);
+        if let Some(worktree) = from_worktree {
+            cmd.arg(worktree);
+        }
+        let output = logger::output(&mut cmd)?;
+
+        if !output.status.success() {
+            return Err(anyhow!("Failed to repair worktree {}: {}",
+                path.display(), String::from_utf8_lossy(&output.stderr)));
+        }
+
+        Ok(())
+    }
+
+    // worktree 中的檔案：已追蹤與未追蹤的檔案，可選擇包含被 .gitignore 排除的檔案
+    pub fn list_files(&self, path: &Path, include_ignored: bool) -> Result<Vec<PathBuf>> {
+        let mut cmd = Command::new("git");
+        cmd.args(["ls-files", "-z", "--cached", "--others"]);
+        if !include_ignored {
+            cmd.arg("--exclude-standard");
+        }
+
+        let output = logger::output(cmd.current_dir(path))?;
+        if !output.status.success() {
+            return Err(anyhow!("Failed to list files in {}: {}",
+                path.display(), String::from_utf8_lossy(&output.stderr)));
+        }
+
+        let mut files: Vec<PathBuf> = String::from_utf8_lossy(&output.stdout)
+            .split('\0')
+            .filter(|name| !name.is_empty())
+            .map(PathBuf::from)
+            // 已從工作目錄刪除的已追蹤檔案不存在，略過
+            .filter(|name| fs::symlink_metadata(path.join(name)).is_ok())
+            .collect();
+        files.sort();
+        files.dedup();
+        Ok(files)
+    }
+
+    // 將索引重設為 HEAD，不改動工作目錄中的檔案
+    pub fn reset_index(&self, path: &Path) -> Result<()> {
+        let output = logger::output(Command::new("git")
+            .args(["reset", "--quiet"])
+            .current_dir(path))?;
+
+        if !output.status.success() {
+            return Err(anyhow!("Failed to reset the index of {}: {}",
+                path.display(), String::from_utf8_lossy(&output.stderr)));
+        }
+
+        Ok(())
+    }
+
+    fn describe_commit(&self, commit: &str) -> Option<String> {
+        let output = logger::output(Command::new("git")
+            .args(["describe", "--tags", commit])
+            .current_dir(&self.repo_path))
+            .ok()?;
+
+        if !output.status.success() {
+            return None;
+        }
+
+        let describe = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        (!describe.is_empty()).then_some(describe)
+    }
+
+    pub fn move_worktree(&self, from: &Path, to: &Path) -> Result<()> {
+        if dry_run::is_enabled() {
+            dry_run::record(PlannedAction::MoveWorktree { from: from.to_path_buf(), to: to.to_path_buf() });
+            return Ok(());
+        }
+
+        let output = logger::output(Command::new("git")
+            .args(["worktree", "move", &from.to_string_lossy(), &to.to_string_lossy()])
+            .current_dir(&self.repo_path))?;
+
+        if !output.status.success() {
+            return Err(anyhow!("Failed to move worktree {}: {}", from.display(), String::from_utf8_lossy(&output.stderr)));
+        }
+
+        Ok(())
+    }
+
+    pub fn remove_worktree(&self, path: &Path, force: bool) -> Result<()> {
+        if dry_run::is_enabled() {
+            dry_run::record(PlannedAction::RemoveWorktree { path: path.to_path_buf() });
+            return Ok(());
+        }
+
+        // 使用 git worktree remove 命令；--force 允許移除有未提交變更的 worktree
+        let mut cmd = Command::new("git");
+        cmd.args(["worktree", "remove"]);
+        if force {
+            cmd.arg("--force");
+        }
+        let output = logger::output(cmd.arg(path.to_string_lossy().as_ref()).current_dir(&self.repo_path))?;
 
         if !output.status.success() {
             let error_message = String::from_utf8_lossy(&output.stderr);
@@ -170,4 +485,41 @@ impl WorktreeManager {
 
         Ok(())
     }
+}
+
+fn plan_add_worktree(path: &Path, branch: &str, branch_mode: &BranchMode, base_branch: Option<&str>) {
+    if let Some(parent) = path.parent().filter(|parent| !parent.exists() && !dry_run::creates(parent)) {
+        dry_run::record(PlannedAction::CreateDir { path: parent.to_path_buf() });
+    }
+
+    if *branch_mode == BranchMode::NewBranch {
+        dry_run::record(PlannedAction::CreateBranch {
+            branch: branch.to_string(),
+            from: base_branch.unwrap_or("HEAD").to_string(),
+        });
+    }
+
+    dry_run::record(PlannedAction::AddWorktree {
+        path: path.to_path_buf(),
+        reference: branch.to_string(),
+        detached: *branch_mode == BranchMode::Detached,
+    });
+}
+
+// 標記目前所在的 worktree
+fn mark_current_worktree(worktrees: &mut [WorktreeInfo], current: &Path) {
+    let current = current.canonicalize().unwrap_or_else(|_| current.to_path_buf());
+
+    for wt in worktrees.iter_mut() {
+        let path = wt.path.canonicalize().unwrap_or_else(|_| wt.path.clone());
+        wt.is_current = path == current;
+    }
+}
+
+fn git_in(path: &Path, args: &[&str], action: &str) -> Result<()> {
+    let output = logger::output(Command::new("git").args(args).current_dir(path))?;
+    if !output.status.success() {
+        return Err(anyhow!("Failed to {} in {}: {}", action, path.display(), String::from_utf8_lossy(&output.stderr)));
+    }
+    Ok(())
 }
\ No newline at end of file
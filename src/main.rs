@@ -7,23 +7,93 @@ mod git;
 mod ui;
 mod utils;
 mod error;
+mod config;
 
 use cli::{Cli, Commands};
+use error::WtError;
 use git::repository::Repository;
+use ui::colors::Theme;
 use ui::display::Display;
+use utils::dry_run;
+use utils::logger::{self, Level};
 
-fn main() -> Result<()> {
+fn main() {
     let cli = Cli::parse();
+    Display::init(cli.global.output, cli.global.quiet);
 
-    // Check if we're in a git repository
-    if !Repository::is_git_repository() {
-        Display::show_error("Not in a Git repository");
+    if let Err(e) = logger::init(log_level(&cli), cli.global.log_file.as_deref()) {
+        Display::show_failure(&e);
         std::process::exit(1);
     }
+    dry_run::set_enabled(cli.global.dry_run);
+    logger::debug(&format!("wt {} {:?}", env!("CARGO_PKG_VERSION"), std::env::args().collect::<Vec<_>>()));
+
+    if let Err(e) = run(cli) {
+        Display::show_failure(&e);
+        std::process::exit(1);
+    }
+}
+
+// -q / -v 優先於 WT_LOG 環境變數
+fn log_level(cli: &Cli) -> Level {
+    if cli.global.quiet {
+        return Level::Error;
+    }
+    match cli.global.verbose {
+        0 => std::env::var("WT_LOG").ok()
+            .and_then(|value| Level::parse(&value))
+            .unwrap_or(Level::Warn),
+        1 => Level::Debug,
+        _ => Level::Trace,
+    }
+}
+
+fn run(cli: Cli) -> Result<()> {
+    // -C 的行為與 git 相同：先切換工作目錄，之後的相對路徑都以它為準
+    if let Some(dir) = &cli.global.directory {
+        std::env::set_current_dir(dir)
+            .map_err(|e| anyhow::anyhow!("Cannot change to '{}': {}", dir.display(), e))?;
+    }
+
+    if let Some(repo) = &cli.global.repo {
+        let repo = std::env::current_dir()?.join(repo);
+        Repository::set_discovery_root(repo);
+    }
+
+    // 顏色可在倉庫設定檔中調整；不在倉庫中時使用預設值
+    let theme_config = Repository::open_current().ok()
+        .and_then(|repo| repo.load_config().ok())
+        .map(|config| config.theme)
+        .unwrap_or_default();
+    Theme::init(Theme::new(cli.global.color, &theme_config)?);
+
+    // Check if we're in a git repository (clone creates one; completions don't need one)
+    let needs_repository = !matches!(
+        cli.command,
+        Commands::Clone(_) | Commands::Completions(_) | Commands::Complete(_)
+    );
+    if needs_repository && !Repository::is_git_repository() {
+        return Err(WtError::NotGitRepository.into());
+    }
 
     match cli.command {
         Commands::Add(cmd) => cmd.execute(),
         Commands::Remove(cmd) => cmd.execute(),
         Commands::Open(cmd) => cmd.execute(),
+        Commands::Status(cmd) => cmd.execute(),
+        Commands::Clone(cmd) => cmd.execute(),
+        Commands::Undo(cmd) => cmd.execute(),
+        Commands::Restore(cmd) => cmd.execute(),
+        Commands::Snapshots(cmd) => cmd.execute(),
+        Commands::Archive(cmd) => cmd.execute(),
+        Commands::Unarchive(cmd) => cmd.execute(),
+        Commands::Stack(cmd) => cmd.execute(),
+        Commands::Restack(cmd) => cmd.execute(),
+        Commands::Sparse(cmd) => cmd.execute(),
+        Commands::Env(cmd) => cmd.execute(),
+        Commands::Adopt(cmd) => cmd.execute(),
+        Commands::Doctor(cmd) => cmd.execute(),
+        Commands::Completions(cmd) => cmd.execute(),
+        Commands::Complete(cmd) => cmd.execute(),
     }
 }
\ No newline at end of file
@@ -1,34 +1,233 @@
-use owo_colors::OwoColorize;
+use anyhow::{Result, anyhow};
+use owo_colors::{AnsiColors, OwoColorize, Style};
 use is_terminal::IsTerminal;
+use std::env;
+use std::sync::OnceLock;
+use crate::config::ThemeConfig;
 
-pub fn init_colors() {
-    // 自動偵測終端是否支援顏色
-    if !std::io::stdout().is_terminal() {
-        // 如果不是 TTY，可以考慮禁用顏色
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Success,
+    Error,
+    Info,
+    Warning,
+    Dim,
+}
+
+// 所有顏色與符號都由同一個 theme 決定
+pub struct Theme {
+    colors_enabled: bool,
+    unicode: bool,
+    success: Style,
+    error: Style,
+    info: Style,
+    warning: Style,
+    dim: Style,
+}
+
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+impl Theme {
+    pub fn new(choice: ColorChoice, config: &ThemeConfig) -> Result<Self> {
+        Ok(Self {
+            colors_enabled: colors_enabled(choice),
+            unicode: locale_is_utf8(),
+            success: parse_style(&config.success)?,
+            error: parse_style(&config.error)?,
+            info: parse_style(&config.info)?,
+            warning: parse_style(&config.warning)?,
+            dim: parse_style(&config.dim)?,
+        })
+    }
+
+    pub fn init(theme: Theme) {
+        // inquire 的提示也要遵守顏色設定
+        if !theme.colors_enabled {
+            inquire::set_global_render_config(inquire::ui::RenderConfig::empty());
+        }
+        let _ = THEME.set(theme);
+    }
+
+    pub fn current() -> &'static Theme {
+        THEME.get_or_init(|| Theme::new(ColorChoice::Auto, &ThemeConfig::default())
+            .expect("default theme is valid"))
+    }
+
+    pub fn paint(&self, text: &str, role: Role) -> String {
+        if !self.colors_enabled {
+            return text.to_string();
+        }
+        let style = match role {
+            Role::Success => self.success,
+            Role::Error => self.error,
+            Role::Info => self.info,
+            Role::Warning => self.warning,
+            Role::Dim => self.dim,
+        };
+        text.style(style).to_string()
+    }
+
+    pub fn glyph(&self, role: Role) -> &'static str {
+        match (role, self.unicode) {
+            (Role::Success, true) => "✓",
+            (Role::Success, false) => "[ok]",
+            (Role::Error, true) => "✗",
+            (Role::Error, false) => "[error]",
+            (Role::Info, true) => "ℹ",
+            (Role::Info, false) => "[info]",
+            (Role::Warning, true) => "⚠",
+            (Role::Warning, false) => "[warn]",
+            (Role::Dim, _) => "",
+        }
+    }
+
+    pub fn bullet(&self) -> &'static str {
+        if self.unicode { "•" } else { "-" }
+    }
+
+    pub fn hint(&self) -> &'static str {
+        if self.unicode { "💡" } else { "Hint:" }
+    }
+
+    pub fn ellipsis(&self) -> &'static str {
+        if self.unicode { "…" } else { "..." }
+    }
+
+    // 選單中分組標題兩側的線段
+    pub fn rule(&self) -> &'static str {
+        if self.unicode { "──" } else { "--" }
+    }
+
+    // 樹狀顯示中連到節點的線段，以及其子節點的縮排
+    pub fn tree_branch(&self, last: bool) -> &'static str {
+        match (self.unicode, last) {
+            (true, false) => "├─ ",
+            (true, true) => "└─ ",
+            (false, false) => "|- ",
+            (false, true) => "`- ",
+        }
+    }
+
+    pub fn tree_indent(&self, last: bool) -> &'static str {
+        match (self.unicode, last) {
+            (true, false) => "│  ",
+            (false, false) => "|  ",
+            (_, true) => "   ",
+        }
     }
 }
 
+fn colors_enabled(choice: ColorChoice) -> bool {
+    match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            // https://no-color.org 與 https://bixense.com/clicolors
+            if env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+                return false;
+            }
+            if env::var("CLICOLOR_FORCE").is_ok_and(|v| !v.is_empty() && v != "0") {
+                return true;
+            }
+            if env::var("TERM").is_ok_and(|term| term == "dumb") {
+                return false;
+            }
+            std::io::stdout().is_terminal()
+        }
+    }
+}
+
+fn locale_is_utf8() -> bool {
+    if cfg!(windows) {
+        return true;
+    }
+
+    // 依照 POSIX 的優先順序取第一個有設定的 locale 變數
+    ["LC_ALL", "LC_CTYPE", "LANG"].iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|value| !value.is_empty())
+        .map(|value| {
+            let value = value.to_lowercase();
+            value.contains("utf-8") || value.contains("utf8")
+        })
+        .unwrap_or(false)
+}
+
+// 解析設定檔中的樣式，例如 "green"、"bold red"、"dimmed"、"none"
+fn parse_style(spec: &str) -> Result<Style> {
+    let mut style = Style::new();
+
+    for word in spec.split_whitespace() {
+        style = match word.to_lowercase().as_str() {
+            "none" => style,
+            "bold" => style.bold(),
+            "dimmed" | "dim" => style.dimmed(),
+            "italic" => style.italic(),
+            "underline" => style.underline(),
+            color => style.color(parse_color(color)
+                .ok_or_else(|| anyhow!("Unknown color '{}' in theme", word))?),
+        };
+    }
+
+    Ok(style)
+}
+
+fn parse_color(name: &str) -> Option<AnsiColors> {
+    let color = match name {
+        "black" => AnsiColors::Black,
+        "red" => AnsiColors::Red,
+        "green" => AnsiColors::Green,
+        "yellow" => AnsiColors::Yellow,
+        "blue" => AnsiColors::Blue,
+        "magenta" => AnsiColors::Magenta,
+        "cyan" => AnsiColors::Cyan,
+        "white" => AnsiColors::White,
+        "bright_black" | "gray" | "grey" => AnsiColors::BrightBlack,
+        "bright_red" => AnsiColors::BrightRed,
+        "bright_green" => AnsiColors::BrightGreen,
+        "bright_yellow" => AnsiColors::BrightYellow,
+        "bright_blue" => AnsiColors::BrightBlue,
+        "bright_magenta" => AnsiColors::BrightMagenta,
+        "bright_cyan" => AnsiColors::BrightCyan,
+        "bright_white" => AnsiColors::BrightWhite,
+        _ => return None,
+    };
+    Some(color)
+}
+
 pub trait ColorizeExt {
     fn success(&self) -> String;
     fn error(&self) -> String;
     fn info(&self) -> String;
     fn warning(&self) -> String;
+    fn dim(&self) -> String;
 }
 
 impl ColorizeExt for str {
     fn success(&self) -> String {
-        self.green().to_string()
+        Theme::current().paint(self, Role::Success)
     }
 
     fn error(&self) -> String {
-        self.red().to_string()
+        Theme::current().paint(self, Role::Error)
     }
 
     fn info(&self) -> String {
-        self.blue().to_string()
+        Theme::current().paint(self, Role::Info)
     }
 
     fn warning(&self) -> String {
-        self.yellow().to_string()
+        Theme::current().paint(self, Role::Warning)
+    }
+
+    fn dim(&self) -> String {
+        Theme::current().paint(self, Role::Dim)
     }
 }
\ No newline at end of file
@@ -1,17 +1,212 @@
-use owo_colors::OwoColorize;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::sync::{Mutex, OnceLock};
+use crate::error::WtError;
+use crate::ui::colors::{Role, Theme};
+use crate::utils::dry_run;
+use crate::utils::logger::{self, Level};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    Human,
+    Json,
+}
+
+// 所有輸出都經過 reporter，讓同一個指令可以輸出給人看的文字或 JSON
+pub trait Reporter: Send + Sync {
+    fn success(&self, message: &str);
+    fn error(&self, message: &str);
+    fn info(&self, message: &str);
+    fn warning(&self, message: &str);
+    fn result(&self, command: &str, result: Value);
+    fn failure(&self, code: &str, message: &str);
+    fn is_human(&self) -> bool;
+}
+
+pub struct HumanReporter {
+    // 安靜模式只顯示錯誤與警告
+    quiet: bool,
+}
+
+impl HumanReporter {
+    fn prefix(role: Role) -> String {
+        let theme = Theme::current();
+        theme.paint(theme.glyph(role), role)
+    }
+}
+
+impl Reporter for HumanReporter {
+    fn success(&self, message: &str) {
+        // 乾跑時沒有實際完成任何操作
+        if !self.quiet && !dry_run::is_enabled() {
+            println!("{} {}", Self::prefix(Role::Success), message);
+        }
+    }
+
+    fn error(&self, message: &str) {
+        eprintln!("{} {}", Self::prefix(Role::Error), message);
+    }
+
+    fn info(&self, message: &str) {
+        if !self.quiet {
+            println!("{} {}", Self::prefix(Role::Info), message);
+        }
+    }
+
+    fn warning(&self, message: &str) {
+        eprintln!("{} {}", Self::prefix(Role::Warning), message);
+    }
+
+    fn result(&self, _command: &str, _result: Value) {
+        // 人類可讀模式下，結果已經透過訊息顯示；乾跑時列出計畫的操作
+        if !dry_run::is_enabled() {
+            return;
+        }
+
+        let plan = dry_run::planned_actions();
+        if plan.is_empty() {
+            self.info("Dry run: nothing to do");
+            return;
+        }
+
+        println!("{} Dry run, planned actions:", Self::prefix(Role::Info));
+        for (index, action) in plan.iter().enumerate() {
+            println!("  {}. {}", index + 1, action);
+        }
+    }
+
+    fn failure(&self, _code: &str, message: &str) {
+        self.error(message);
+    }
+
+    fn is_human(&self) -> bool {
+        true
+    }
+}
+
+// JSON 模式只在結束時輸出一份文件；過程中的警告與非致命錯誤會收集到 warnings
+#[derive(Default)]
+pub struct JsonReporter {
+    warnings: Mutex<Vec<String>>,
+}
+
+impl JsonReporter {
+    fn take_warnings(&self) -> Vec<String> {
+        self.warnings.lock().map(|mut w| std::mem::take(&mut *w)).unwrap_or_default()
+    }
+
+    fn push_warning(&self, message: &str) {
+        if let Ok(mut warnings) = self.warnings.lock() {
+            warnings.push(message.to_string());
+        }
+    }
+}
+
+impl Reporter for JsonReporter {
+    fn success(&self, _message: &str) {}
+
+    fn error(&self, message: &str) {
+        self.push_warning(message);
+    }
+
+    fn info(&self, _message: &str) {}
+
+    fn warning(&self, message: &str) {
+        self.push_warning(message);
+    }
+
+    fn result(&self, command: &str, result: Value) {
+        let mut document = json!({
+            "ok": true,
+            "command": command,
+            "result": result,
+            "warnings": self.take_warnings(),
+        });
+        if dry_run::is_enabled() {
+            document["dry_run"] = json!(true);
+            document["plan"] = json!(dry_run::planned_actions());
+        }
+        println!("{}", document);
+    }
+
+    fn failure(&self, code: &str, message: &str) {
+        let document = json!({
+            "ok": false,
+            "error": {
+                "code": code,
+                "message": message,
+            },
+            "warnings": self.take_warnings(),
+        });
+        println!("{}", document);
+    }
+
+    fn is_human(&self) -> bool {
+        false
+    }
+}
+
+static REPORTER: OnceLock<Box<dyn Reporter>> = OnceLock::new();
 
 pub struct Display;
 
 impl Display {
+    pub fn init(format: OutputFormat, quiet: bool) {
+        let reporter: Box<dyn Reporter> = match format {
+            OutputFormat::Human => Box::new(HumanReporter { quiet }),
+            OutputFormat::Json => Box::new(JsonReporter::default()),
+        };
+        let _ = REPORTER.set(reporter);
+    }
+
+    fn reporter() -> &'static dyn Reporter {
+        REPORTER.get_or_init(|| Box::new(HumanReporter { quiet: false })).as_ref()
+    }
+
+    pub fn is_human() -> bool {
+        Self::reporter().is_human()
+    }
+
     pub fn show_success(message: &str) {
-        println!("{} {}", "✓".green(), message);
+        Self::reporter().success(message);
     }
 
     pub fn show_error(message: &str) {
-        eprintln!("{} {}", "✗".red(), message);
+        logger::log(Level::Error, message);
+        Self::reporter().error(message);
     }
 
     pub fn show_info(message: &str) {
-        println!("{} {}", "ℹ".blue(), message);
+        Self::reporter().info(message);
+    }
+
+    pub fn show_warning(message: &str) {
+        Self::reporter().warning(message);
+    }
+
+    pub fn show_result<T: Serialize>(command: &str, result: &T) {
+        let value = serde_json::to_value(result).unwrap_or(Value::Null);
+        Self::reporter().result(command, value);
+    }
+
+    pub fn show_failure(error: &anyhow::Error) {
+        logger::debug(&format!("failed: {:#}", error));
+        Self::reporter().failure(error_code(error), &error.to_string());
     }
 }
+
+fn error_code(error: &anyhow::Error) -> &'static str {
+    if let Some(wt_error) = error.downcast_ref::<WtError>() {
+        return wt_error.code();
+    }
+
+    match error.downcast_ref::<inquire::InquireError>() {
+        Some(inquire::InquireError::OperationCanceled)
+        | Some(inquire::InquireError::OperationInterrupted) => "user_cancelled",
+        Some(inquire::InquireError::NotTTY) => "not_a_terminal",
+        Some(_) => "prompt_error",
+        None if error.downcast_ref::<git2::Error>().is_some() => "git_error",
+        None if error.downcast_ref::<std::io::Error>().is_some() => "io_error",
+        None => "error",
+    }
+}
\ No newline at end of file
@@ -1,13 +1,30 @@
 use anyhow::Result;
+use fuzzy_matcher::FuzzyMatcher;
+use fuzzy_matcher::skim::SkimMatcherV2;
 use inquire::{Select, Text, Confirm};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use crate::config::{NamingConfig, Opener, PickerConfig};
+use crate::ui::colors::{Role, Theme};
 use crate::git::{branches::Branch, worktree::{BranchMode, WorktreeInfo}};
-use crate::utils::validation::validate_branch_name;
+use crate::utils::paths::{is_path_taken, normalize_path};
+use crate::utils::time::format_relative_time;
+use crate::utils::validation::validate_path_name;
+use crate::error::WtResult;
+use crate::utils::naming::{build_branch_name, check_branch_name, slugify, uses_field, validate_ticket, validate_type, Field};
 
 pub struct Prompts;
 
+#[derive(Debug, Clone, PartialEq)]
+pub enum CheckedOutAction {
+    OpenExisting,
+    BranchOff,
+    PickAnother,
+}
+
 impl Prompts {
     pub fn select_branch_mode() -> Result<BranchMode> {
-        let options = vec!["Create new branch", "Use existing branch"];
+        let options = vec!["Create new branch", "Use existing branch", "Detached HEAD at a tag, commit or ref"];
         let selection = Select::new("Would you like to create a new branch or use an existing one?", options)
             .with_help_message("Use arrow keys to navigate, Enter to select")
             .prompt()?;
@@ -15,117 +32,387 @@ impl Prompts {
         match selection {
             "Create new branch" => Ok(BranchMode::NewBranch),
             "Use existing branch" => Ok(BranchMode::ExistingBranch),
+            "Detached HEAD at a tag, commit or ref" => Ok(BranchMode::Detached),
             _ => unreachable!(),
         }
     }
 
-    pub fn input_branch_name() -> Result<String> {
-        let branch_name = Text::new("Enter new branch name:")
+    pub fn input_branch_name(naming: &NamingConfig) -> Result<String> {
+        // 設定了命名模板時先依欄位組出名稱，再讓使用者確認或修改
+        let suggested = match &naming.template {
+            Some(template) => Some(Self::input_templated_branch_name(naming, template)?),
+            None => None,
+        };
+
+        let policy = naming.clone();
+        let mut prompt = Text::new("Enter new branch name:")
             .with_help_message("Branch name should not contain spaces or special characters")
+            .with_validator(move |input: &str| Ok(to_validation(check_branch_name(input, &policy))));
+        if let Some(suggested) = &suggested {
+            prompt = prompt.with_initial_value(suggested);
+        }
+
+        Ok(prompt.prompt()?)
+    }
+
+    fn input_templated_branch_name(naming: &NamingConfig, template: &str) -> Result<String> {
+        let kind = if uses_field(template, Field::Type) {
+            Some(if naming.types.is_empty() {
+                let policy = naming.clone();
+                Text::new("Type:")
+                    .with_validator(move |input: &str| Ok(to_validation(validate_type(&policy, input))))
+                    .prompt()?
+            } else {
+                Select::new("Type:", naming.types.clone())
+                    .with_help_message("Use arrow keys to navigate, Enter to select")
+                    .prompt()?
+            })
+        } else {
+            None
+        };
+
+        let ticket = if uses_field(template, Field::Ticket) {
+            let policy = naming.clone();
+            Some(Text::new("Ticket:")
+                .with_help_message(&format!("Must match {}", naming.ticket_pattern))
+                .with_validator(move |input: &str| Ok(to_validation(validate_ticket(&policy, input.trim()))))
+                .prompt()?
+                .trim()
+                .to_string())
+        } else {
+            None
+        };
+
+        let summary = if uses_field(template, Field::Summary) {
+            Some(Text::new("Summary:")
+                .with_help_message("A few words describing the change")
+                .with_validator(|input: &str| {
+                    if slugify(input).is_empty() {
+                        Ok(inquire::validator::Validation::Invalid("Summary must contain letters or digits".into()))
+                    } else {
+                        Ok(inquire::validator::Validation::Valid)
+                    }
+                })
+                .prompt()?)
+        } else {
+            None
+        };
+
+        Ok(build_branch_name(naming, template, kind.as_deref(), ticket.as_deref(), summary.as_deref())?)
+    }
+
+    pub fn input_ref() -> Result<String> {
+        let reference = Text::new("Enter a tag, commit SHA or ref:")
+            .with_help_message("The worktree will be checked out with a detached HEAD")
             .with_validator(|input: &str| {
-                match validate_branch_name(input) {
-                    Ok(_) => Ok(inquire::validator::Validation::Valid),
-                    Err(e) => Ok(inquire::validator::Validation::Invalid(
-                        inquire::validator::ErrorMessage::Custom(e.to_string())
-                    )),
+                if input.trim().is_empty() {
+                    Ok(inquire::validator::Validation::Invalid("Ref cannot be empty".into()))
+                } else {
+                    Ok(inquire::validator::Validation::Valid)
                 }
             })
             .prompt()?;
 
-        Ok(branch_name)
+        Ok(reference.trim().to_string())
     }
 
-    pub fn select_base_branch(branches: Vec<Branch>) -> Result<Branch> {
-        if branches.is_empty() {
-            return Err(anyhow::anyhow!("No branches available"));
-        }
-
-        let branch_names: Vec<String> = branches.iter().map(|b| {
-            if b.is_remote {
-                format!("{} (remote)", b.name)
-            } else {
-                b.name.clone()
-            }
-        }).collect();
+    pub fn select_base_branch(branches: Vec<Branch>, config: &PickerConfig) -> Result<Branch> {
+        select_branch(
+            "Select base branch:",
+            "This will be the starting point for your new branch (type to filter)",
+            branches,
+            config,
+        )
+    }
 
-        let selection = Select::new("Select base branch:", branch_names.clone())
-            .with_help_message("This will be the starting point for your new branch")
-            .prompt()?;
+    pub fn select_existing_branch(branches: Vec<Branch>, config: &PickerConfig) -> Result<Branch> {
+        select_branch(
+            "Select existing branch:",
+            "Choose the branch to create a worktree for (type to filter)",
+            branches,
+            config,
+        )
+    }
 
-        // 找到對應的分支
-        let index = branch_names.iter().position(|name| name == &selection)
-            .ok_or_else(|| anyhow::anyhow!("Selected branch not found"))?;
+    pub fn select_checked_out_action(branch: &str, worktree_path: &Path, allow_pick_another: bool) -> Result<CheckedOutAction> {
+        let open_label = "Open the existing worktree in VSCode";
+        let branch_off_label = format!("Create a new branch based on '{}'", branch);
+        let pick_label = "Pick another branch";
+        let mut options = vec![open_label.to_string(), branch_off_label.clone()];
+        if allow_pick_another {
+            options.push(pick_label.to_string());
+        }
 
-        Ok(branches[index].clone())
-    }
+        let message = format!(
+            "Branch '{}' is already checked out at {}",
+            branch,
+            worktree_path.display()
+        );
+        let selection = Select::new(&message, options)
+            .with_help_message("A branch can only be checked out in one worktree at a time")
+            .prompt()?;
 
-    pub fn select_existing_branch(branches: Vec<Branch>) -> Result<Branch> {
-        if branches.is_empty() {
-            return Err(anyhow::anyhow!("No branches available"));
+        if selection == open_label {
+            Ok(CheckedOutAction::OpenExisting)
+        } else if selection == branch_off_label {
+            Ok(CheckedOutAction::BranchOff)
+        } else {
+            Ok(CheckedOutAction::PickAnother)
         }
+    }
 
-        let branch_names: Vec<String> = branches.iter().map(|b| {
-            if b.is_remote {
-                format!("{} (remote)", b.name)
-            } else {
-                b.name.clone()
-            }
-        }).collect();
+    // 回傳選擇的修復方式索引；選擇略過時為 None
+    pub fn select_fix(issue: &str, fixes: &[String]) -> Result<Option<usize>> {
+        let skip_label = "Skip".to_string();
+        let mut options = fixes.to_vec();
+        options.push(skip_label.clone());
 
-        let selection = Select::new("Select existing branch:", branch_names.clone())
-            .with_help_message("Choose the branch to create a worktree for")
+        let selection = Select::new(issue, options)
+            .with_help_message("Choose how to fix this issue")
             .prompt()?;
 
-        // 找到對應的分支
-        let index = branch_names.iter().position(|name| name == &selection)
-            .ok_or_else(|| anyhow::anyhow!("Selected branch not found"))?;
-
-        Ok(branches[index].clone())
+        Ok(fixes.iter().position(|fix| *fix == selection))
     }
 
-    pub fn select_worktree(worktrees: Vec<WorktreeInfo>) -> Result<WorktreeInfo> {
+    pub fn select_worktree(worktrees: Vec<WorktreeInfo>, config: &PickerConfig) -> Result<WorktreeInfo> {
         if worktrees.is_empty() {
             return Err(anyhow::anyhow!("No worktrees available"));
         }
 
-        let worktree_descriptions: Vec<String> = worktrees.iter().map(|wt| {
-            format!("{} ({})", wt.branch, wt.path.display())
+        let mut worktrees = worktrees;
+        // 當前所在的 worktree 優先，其餘依分支名稱排序
+        worktrees.sort_by(|a, b| b.is_current.cmp(&a.is_current).then_with(|| a.display_name().cmp(&b.display_name())));
+
+        let items: Vec<PickerItem> = worktrees.iter().enumerate().map(|(index, wt)| {
+            PickerItem {
+                label: format!("{} ({})", wt.display_name(), wt.path.display()),
+                key: format!("{} {}", wt.display_name(), wt.path.display()),
+                kind: ItemKind::Entry(index),
+            }
         }).collect();
 
-        let selection = Select::new("Select worktree:", worktree_descriptions.clone())
-            .with_help_message("Choose the worktree to work with")
+        let scorer = picker_scorer(usize::MAX);
+        let selection = Select::new("Select worktree:", items)
+            .with_help_message("Choose the worktree to work with (type to filter)")
+            .with_page_size(config.page_size)
+            .with_scorer(&scorer)
             .prompt()?;
 
-        // 找到對應的 worktree
-        let index = worktree_descriptions.iter().position(|desc| desc == &selection)
-            .ok_or_else(|| anyhow::anyhow!("Selected worktree not found"))?;
-
-        Ok(worktrees[index].clone())
+        match selection.kind {
+            ItemKind::Entry(index) => Ok(worktrees[index].clone()),
+            _ => Err(anyhow::anyhow!("Selected worktree not found")),
+        }
     }
 
     pub fn confirm_removal(worktree: &WorktreeInfo) -> Result<bool> {
         let message = format!(
             "Are you sure you want to remove the worktree '{}'?\nPath: {}",
-            worktree.branch,
+            worktree.display_name(),
             worktree.path.display()
         );
 
         let confirmed = Confirm::new(&message)
             .with_default(false)
-            .with_help_message("This action cannot be undone")
+            .with_help_message("Run 'wt undo' afterwards to restore it")
             .prompt()?;
 
         Ok(confirmed)
     }
 
-    pub fn confirm_vscode_open(path: &str) -> Result<bool> {
-        let message = format!("Open worktree in VSCode?\nPath: {}", path);
+    // 複製下來的倉庫自帶設定時，列出會執行的步驟並預設拒絕
+    pub fn confirm_repo_config(steps: &[String]) -> Result<bool> {
+        let bullet = Theme::current().bullet();
+        let list: Vec<String> = steps.iter().map(|step| format!("  {} {}", bullet, step)).collect();
+        let message = format!("The cloned repository's .wt.toml wants to:\n{}\nRun these steps?", list.join("\n"));
+
+        let confirmed = Confirm::new(&message)
+            .with_default(false)
+            .with_help_message("Only continue if you trust this repository")
+            .prompt()?;
+
+        Ok(confirmed)
+    }
+
+    // 預設路徑已被使用時，讓使用者選擇加上後綴的路徑或自行輸入；取消時回傳 None
+    pub fn resolve_path_collision(path: &Path, suggestion: &Path, registered: &[PathBuf]) -> Result<Option<PathBuf>> {
+        let use_suggestion = format!("Use {}", suggestion.display());
+        let options = vec![use_suggestion.clone(), "Enter another path".to_string(), "Cancel".to_string()];
+        let selection = Select::new(&format!("{} is already in use. What would you like to do?", path.display()), options)
+            .with_help_message("Use arrow keys to navigate, Enter to select")
+            .prompt()?;
+
+        if selection == use_suggestion {
+            return Ok(Some(suggestion.to_path_buf()));
+        }
+        if selection == "Cancel" {
+            return Ok(None);
+        }
+
+        let base = std::env::current_dir()?;
+        let registered = registered.to_vec();
+        let input = Text::new("Worktree path:")
+            .with_initial_value(&suggestion.to_string_lossy())
+            .with_validator(move |input: &str| {
+                let candidate = normalize_path(&base.join(input));
+                if is_path_taken(&candidate, &registered) {
+                    return Ok(inquire::validator::Validation::Invalid(
+                        format!("{} is already in use", candidate.display()).into()));
+                }
+                Ok(to_validation(validate_path_name(&candidate)))
+            })
+            .prompt()?;
+
+        Ok(Some(normalize_path(&std::env::current_dir()?.join(input))))
+    }
+
+    pub fn confirm_open(path: &str, opener: Opener) -> Result<bool> {
+        let (target, help) = match opener {
+            Opener::Vscode => ("VSCode", "This will launch VSCode with the worktree directory"),
+            Opener::Tmux => ("tmux", "This will create or reuse a tmux session in the worktree directory"),
+        };
+        let message = format!("Open worktree in {}?\nPath: {}", target, path);
 
         let confirmed = Confirm::new(&message)
             .with_default(true)
-            .with_help_message("This will launch VSCode with the worktree directory")
+            .with_help_message(help)
             .prompt()?;
 
         Ok(confirmed)
     }
+}
+
+// 選單項目：label 為顯示文字，key 為模糊搜尋比對的文字
+struct PickerItem {
+    label: String,
+    key: String,
+    kind: ItemKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ItemKind {
+    // 在 branches 或 worktrees 中的位置
+    Entry(usize),
+    // 分組標題；filtered 為 true 時只在輸入篩選文字後顯示，否則只在未篩選時顯示
+    Header { filtered: bool },
+    ShowAll,
+}
+
+impl fmt::Display for PickerItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.label)
+    }
+}
+
+// 未輸入篩選文字時依原本順序只顯示前 limit 個項目；輸入後對所有項目做模糊比對
+fn picker_scorer(limit: usize) -> impl Fn(&str, &PickerItem, &str, usize) -> Option<i64> {
+    move |input, item, _label, position| {
+        let filtering = !input.is_empty();
+        // inquire 依分數由高到低排列，以負的位置保持原本順序
+        let in_order = Some(-(position as i64));
+        match item.kind {
+            ItemKind::Header { filtered } if filtered => filtering.then_some(i64::MAX),
+            ItemKind::Header { .. } | ItemKind::ShowAll => if filtering { None } else { in_order },
+            ItemKind::Entry(index) if !filtering => (index < limit).then_some(in_order).flatten(),
+            ItemKind::Entry(_) => SkimMatcherV2::default().ignore_case().fuzzy_match(&item.key, input),
+        }
+    }
+}
+
+fn select_branch(message: &str, help: &str, branches: Vec<Branch>, config: &PickerConfig) -> Result<Branch> {
+    if branches.is_empty() {
+        return Err(anyhow::anyhow!("No branches available"));
+    }
+
+    // 本地分支在前、遠端分支在後，各自保持原本的排序
+    let mut branches = branches;
+    branches.sort_by_key(|b| b.is_remote);
+
+    let mut limit = if config.max_items == 0 {
+        branches.len()
+    } else {
+        config.max_items.min(branches.len())
+    };
+
+    loop {
+        let items = build_branch_items(&branches, limit);
+        let scorer = picker_scorer(limit);
+        // 第一個項目一定是標題，游標從第一個分支開始；篩選時不重設游標，才不會停在標題上
+        let selection = Select::new(message, items)
+            .with_help_message(help)
+            .with_page_size(config.page_size)
+            .with_scorer(&scorer)
+            .with_starting_cursor(1)
+            .with_reset_cursor(false)
+            .prompt()?;
+
+        match selection.kind {
+            ItemKind::Entry(index) => return Ok(branches[index].clone()),
+            ItemKind::ShowAll => limit = branches.len(),
+            ItemKind::Header { .. } => {}
+        }
+    }
+}
+
+// 所有分支都放進選單，篩選時才找得到超出 limit 的分支
+fn build_branch_items(branches: &[Branch], limit: usize) -> Vec<PickerItem> {
+    let name_width = branches.iter().map(|b| b.name.chars().count()).max().unwrap_or(0);
+    let ages: Vec<String> = branches.iter().map(|b| format_relative_time(b.last_commit_time)).collect();
+    let age_width = ages.iter().map(|a| a.len()).max().unwrap_or(0);
+    let author_width = branches.iter()
+        .map(|b| b.last_author.as_deref().unwrap_or("-").chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let theme = Theme::current();
+    let header = |text: String, filtered: bool| PickerItem {
+        label: theme.paint(&format!("{} {} {}", theme.rule(), text, theme.rule()), Role::Dim),
+        key: String::new(),
+        kind: ItemKind::Header { filtered },
+    };
+
+    let mut items = vec![header("matching branches".to_string(), true)];
+    for (index, (branch, age)) in branches.iter().zip(ages).enumerate() {
+        // 分組標題只在該組有分支顯示於未篩選的選單時加入
+        let starts_group = index == 0 || branches[index - 1].is_remote != branch.is_remote;
+        if starts_group && index < limit {
+            let (scope, count) = if branch.is_remote {
+                ("Remote", branches.iter().filter(|b| b.is_remote).count())
+            } else {
+                ("Local", branches.iter().filter(|b| !b.is_remote).count())
+            };
+            items.push(header(format!("{} branches ({})", scope, count), false));
+        }
+
+        let worktree = if branch.worktree.is_some() { "[worktree]" } else { "" };
+        let label = format!(
+            "{:<name_width$}  {:<age_width$}  {:<author_width$}  {}",
+            branch.name,
+            age,
+            branch.last_author.as_deref().unwrap_or("-"),
+            worktree,
+        );
+
+        items.push(PickerItem {
+            label: label.trim_end().to_string(),
+            key: branch.name.clone(),
+            kind: ItemKind::Entry(index),
+        });
+    }
+
+    if limit < branches.len() {
+        items.push(PickerItem {
+            label: format!("{} show all {} branches", theme.ellipsis(), branches.len()),
+            key: String::new(),
+            kind: ItemKind::ShowAll,
+        });
+    }
+
+    items
+}
+
+fn to_validation(result: WtResult<()>) -> inquire::validator::Validation {
+    match result {
+        Ok(_) => inquire::validator::Validation::Valid,
+        Err(e) => inquire::validator::Validation::Invalid(inquire::validator::ErrorMessage::Custom(e.to_string())),
+    }
 }
\ No newline at end of file
@@ -0,0 +1,192 @@
+use anyhow::{Result, anyhow};
+use clap::ValueEnum;
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+use crate::utils::time::now_timestamp;
+
+// 封存檔根目錄中記錄 worktree 資訊的檔案，解開時會移除
+pub const MANIFEST_FILE_NAME: &str = ".wt-archive.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum, Serialize)]
+pub enum ArchiveFormat {
+    #[value(name = "tar.gz")]
+    #[serde(rename = "tar.gz")]
+    TarGz,
+    #[serde(rename = "zip")]
+    Zip,
+}
+
+impl ArchiveFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ArchiveFormat::TarGz => "tar.gz",
+            ArchiveFormat::Zip => "zip",
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_string_lossy().to_lowercase();
+        if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(ArchiveFormat::TarGz)
+        } else if name.ends_with(".zip") {
+            Some(ArchiveFormat::Zip)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    // 分離 HEAD 的 worktree 沒有分支
+    pub branch: Option<String>,
+    pub commit: String,
+    // 預設分支以及與它的 merge base
+    pub base: Option<String>,
+    pub base_commit: Option<String>,
+    pub date: String,
+    pub timestamp: i64,
+    // 封存前 worktree 所在的路徑
+    pub path: PathBuf,
+    pub include_ignored: bool,
+}
+
+// files 為相對於 source 的路徑
+pub fn create(format: ArchiveFormat, source: &Path, files: &[PathBuf], manifest: &ArchiveManifest, destination: &Path) -> Result<()> {
+    let manifest = serde_json::to_string_pretty(manifest)?;
+    let file = File::create(destination)?;
+
+    let result = match format {
+        ArchiveFormat::TarGz => create_tar_gz(file, source, files, &manifest),
+        ArchiveFormat::Zip => create_zip(file, source, files, &manifest),
+    };
+
+    // 失敗時不留下不完整的封存檔
+    if result.is_err() {
+        fs::remove_file(destination).ok();
+    }
+    result
+}
+
+fn create_tar_gz(file: File, source: &Path, files: &[PathBuf], manifest: &str) -> Result<()> {
+    let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    builder.follow_symlinks(false);
+
+    let mut header = tar::Header::new_gnu();
+    header.set_size(manifest.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(now_timestamp().max(0) as u64);
+    header.set_cksum();
+    builder.append_data(&mut header, MANIFEST_FILE_NAME, manifest.as_bytes())?;
+
+    for relative in files {
+        builder.append_path_with_name(source.join(relative), relative)?;
+    }
+
+    builder.into_inner()?.finish()?;
+    Ok(())
+}
+
+fn create_zip(file: File, source: &Path, files: &[PathBuf], manifest: &str) -> Result<()> {
+    let mut writer = ZipWriter::new(file);
+    let options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .large_file(true);
+
+    writer.start_file(MANIFEST_FILE_NAME, options)?;
+    writer.write_all(manifest.as_bytes())?;
+
+    for relative in files {
+        let path = source.join(relative);
+        let name = relative.to_string_lossy().replace('\\', "/");
+        let metadata = fs::symlink_metadata(&path)?;
+
+        if metadata.file_type().is_symlink() {
+            let target = fs::read_link(&path)?;
+            writer.add_symlink(name, target.to_string_lossy(), options)?;
+            continue;
+        }
+
+        writer.start_file(name, options.unix_permissions(unix_mode(&metadata)))?;
+        io::copy(&mut File::open(&path)?, &mut writer)?;
+    }
+
+    writer.finish()?;
+    Ok(())
+}
+
+#[cfg(unix)]
+fn unix_mode(metadata: &fs::Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode()
+}
+
+#[cfg(not(unix))]
+fn unix_mode(_metadata: &fs::Metadata) -> u32 {
+    0o644
+}
+
+pub fn read_manifest(archive: &Path) -> Result<ArchiveManifest> {
+    let content = match detect_format(archive)? {
+        ArchiveFormat::TarGz => {
+            let mut tar = tar::Archive::new(GzDecoder::new(File::open(archive)?));
+            let mut content = None;
+            for entry in tar.entries()? {
+                let mut entry = entry?;
+                if entry.path()?.as_ref() == Path::new(MANIFEST_FILE_NAME) {
+                    let mut text = String::new();
+                    entry.read_to_string(&mut text)?;
+                    content = Some(text);
+                    break;
+                }
+            }
+            content
+        }
+        ArchiveFormat::Zip => {
+            let mut zip = ZipArchive::new(File::open(archive)?)?;
+            let mut text = String::new();
+            let found = match zip.by_name(MANIFEST_FILE_NAME) {
+                Ok(mut entry) => {
+                    entry.read_to_string(&mut text)?;
+                    true
+                }
+                Err(_) => false,
+            };
+            found.then_some(text)
+        }
+    };
+
+    let content = content.ok_or_else(|| anyhow!("'{}' is not a wt archive: {} is missing",
+        archive.display(), MANIFEST_FILE_NAME))?;
+    serde_json::from_str(&content)
+        .map_err(|e| anyhow!("Invalid manifest in '{}': {}", archive.display(), e))
+}
+
+pub fn extract(archive: &Path, destination: &Path) -> Result<()> {
+    match detect_format(archive)? {
+        ArchiveFormat::TarGz => {
+            let mut tar = tar::Archive::new(GzDecoder::new(File::open(archive)?));
+            tar.set_preserve_permissions(true);
+            tar.set_overwrite(true);
+            tar.unpack(destination)?;
+        }
+        ArchiveFormat::Zip => {
+            ZipArchive::new(File::open(archive)?)?.extract(destination)?;
+        }
+    }
+
+    fs::remove_file(destination.join(MANIFEST_FILE_NAME)).ok();
+    Ok(())
+}
+
+fn detect_format(archive: &Path) -> Result<ArchiveFormat> {
+    ArchiveFormat::from_path(archive)
+        .ok_or_else(|| anyhow!("Unknown archive format for '{}': expected .tar.gz or .zip", archive.display()))
+}
\ No newline at end of file
@@ -0,0 +1,131 @@
+use serde::Serialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use crate::utils::logger;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static PLAN: Mutex<Vec<PlannedAction>> = Mutex::new(Vec::new());
+
+// 乾跑模式下，所有會修改檔案或倉庫的操作都改為記錄在計畫中
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum PlannedAction {
+    Clone { url: String, path: PathBuf, bare_hub: bool },
+    ConfigureFetch { path: PathBuf },
+    CreateDir { path: PathBuf },
+    CreateBranch { branch: String, from: String },
+    DeleteBranch { branch: String },
+    UpdateBranch { branch: String, commit: String },
+    AddWorktree { path: PathBuf, reference: String, detached: bool },
+    RemoveWorktree { path: PathBuf },
+    MoveWorktree { from: PathBuf, to: PathBuf },
+    PruneWorktrees,
+    RepairWorktree { path: PathBuf },
+    RegisterWorktree { path: PathBuf, branch: String },
+    DeletePath { path: PathBuf },
+    MoveFiles { from: PathBuf, to: PathBuf },
+    Fetch { from: PathBuf, reference: String },
+    WriteFile { path: PathBuf },
+    ExtractArchive { archive: PathBuf, path: PathBuf },
+    CreateSnapshot { worktree: PathBuf, reference: String },
+    ApplySnapshot { reference: String, worktree: PathBuf },
+    RunHook { command: String, cwd: PathBuf },
+    SetParent { branch: String, parent: String },
+    Rebase { branch: String, onto: String },
+    KillTmux { name: String },
+    SetSparse { path: PathBuf, directories: Vec<String> },
+    UpdateSubmodules { path: PathBuf },
+    PullLfs { path: PathBuf },
+}
+
+impl fmt::Display for PlannedAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlannedAction::Clone { url, path, bare_hub } => {
+                let kind = if *bare_hub { "bare hub" } else { "repository" };
+                write!(f, "clone {} as {} into {}", url, kind, path.display())
+            }
+            PlannedAction::ConfigureFetch { path } => {
+                write!(f, "configure {} to fetch all branches", path.display())
+            }
+            PlannedAction::CreateDir { path } => write!(f, "create directory {}", path.display()),
+            PlannedAction::CreateBranch { branch, from } => {
+                write!(f, "create branch '{}' from '{}'", branch, from)
+            }
+            PlannedAction::DeleteBranch { branch } => write!(f, "delete branch '{}'", branch),
+            PlannedAction::UpdateBranch { branch, commit } => {
+                write!(f, "move branch '{}' to {}", branch, &commit[..commit.len().min(7)])
+            }
+            PlannedAction::AddWorktree { path, reference, detached } => {
+                let checkout = if *detached { "detached at" } else { "on branch" };
+                write!(f, "add worktree at {} {} '{}'", path.display(), checkout, reference)
+            }
+            PlannedAction::RemoveWorktree { path } => write!(f, "remove worktree at {}", path.display()),
+            PlannedAction::MoveWorktree { from, to } => write!(f, "move worktree {} to {}", from.display(), to.display()),
+            PlannedAction::PruneWorktrees => write!(f, "prune worktrees whose directories are missing"),
+            PlannedAction::RepairWorktree { path } => write!(f, "repair the links of worktree {}", path.display()),
+            PlannedAction::RegisterWorktree { path, branch } => {
+                write!(f, "register {} as a worktree on branch '{}'", path.display(), branch)
+            }
+            PlannedAction::DeletePath { path } => write!(f, "delete {}", path.display()),
+            PlannedAction::MoveFiles { from, to } => {
+                write!(f, "move the files in {} to {}", from.display(), to.display())
+            }
+            PlannedAction::Fetch { from, reference } => write!(f, "fetch '{}' from {}", reference, from.display()),
+            PlannedAction::WriteFile { path } => write!(f, "write {}", path.display()),
+            PlannedAction::ExtractArchive { archive, path } => {
+                write!(f, "extract {} into {}", archive.display(), path.display())
+            }
+            PlannedAction::CreateSnapshot { worktree, reference } => {
+                write!(f, "save uncommitted changes in {} as {}", worktree.display(), reference)
+            }
+            PlannedAction::ApplySnapshot { reference, worktree } => {
+                write!(f, "apply {} to {}", reference, worktree.display())
+            }
+            PlannedAction::RunHook { command, cwd } => {
+                write!(f, "run hook '{}' in {}", command, cwd.display())
+            }
+            PlannedAction::SetParent { branch, parent } => {
+                write!(f, "record '{}' as the parent of '{}'", parent, branch)
+            }
+            PlannedAction::Rebase { branch, onto } => write!(f, "rebase '{}' onto '{}'", branch, onto),
+            PlannedAction::KillTmux { name } => write!(f, "close tmux '{}'", name),
+            PlannedAction::SetSparse { path, directories } => {
+                write!(f, "limit the checkout of {} to: {}", path.display(), directories.join(", "))
+            }
+            PlannedAction::UpdateSubmodules { path } => write!(f, "initialize submodules in {}", path.display()),
+            PlannedAction::PullLfs { path } => write!(f, "download Git LFS files in {}", path.display()),
+        }
+    }
+}
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+pub fn record(action: PlannedAction) {
+    logger::debug(&format!("dry-run: {}", action));
+    if let Ok(mut plan) = PLAN.lock() {
+        plan.push(action);
+    }
+}
+
+// 計畫中先前的步驟是否已會建立此目錄
+pub fn creates(path: &Path) -> bool {
+    planned_actions().iter().any(|action| match action {
+        PlannedAction::Clone { path: created, .. } | PlannedAction::CreateDir { path: created } => {
+            path.starts_with(created)
+        }
+        _ => false,
+    })
+}
+
+pub fn planned_actions() -> Vec<PlannedAction> {
+    PLAN.lock().map(|plan| plan.clone()).unwrap_or_default()
+}
\ No newline at end of file
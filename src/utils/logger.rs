@@ -0,0 +1,165 @@
+use anyhow::{Result, anyhow};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::{Command, ExitStatus, Output};
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Off,
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    pub fn parse(value: &str) -> Option<Level> {
+        match value.trim().to_lowercase().as_str() {
+            "off" => Some(Level::Off),
+            "error" => Some(Level::Error),
+            "warn" | "warning" => Some(Level::Warn),
+            "info" => Some(Level::Info),
+            "debug" => Some(Level::Debug),
+            "trace" => Some(Level::Trace),
+            _ => None,
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Level::Off => "off",
+            Level::Error => "error",
+            Level::Warn => "warn",
+            Level::Info => "info",
+            Level::Debug => "debug",
+            Level::Trace => "trace",
+        }
+    }
+}
+
+struct Logger {
+    level: Level,
+    // 記錄檔至少保留 debug 等級，方便附在問題回報中
+    file: Option<Mutex<File>>,
+    started: Instant,
+}
+
+static LOGGER: OnceLock<Logger> = OnceLock::new();
+
+pub fn init(level: Level, log_file: Option<&Path>) -> Result<()> {
+    let file = match log_file {
+        Some(path) => Some(Mutex::new(
+            OpenOptions::new().create(true).append(true).open(path)
+                .map_err(|e| anyhow!("Cannot open log file {}: {}", path.display(), e))?,
+        )),
+        None => None,
+    };
+
+    let _ = LOGGER.set(Logger { level, file, started: Instant::now() });
+    Ok(())
+}
+
+fn enabled(level: Level) -> bool {
+    match LOGGER.get() {
+        Some(logger) => level <= logger.level || (logger.file.is_some() && level <= Level::Debug),
+        None => false,
+    }
+}
+
+pub fn log(level: Level, message: &str) {
+    let logger = match LOGGER.get() {
+        Some(logger) => logger,
+        None => return,
+    };
+
+    let line = format!("[{} +{:.3}s] {}", level.label(), logger.started.elapsed().as_secs_f64(), message);
+
+    if level <= logger.level {
+        eprintln!("{}", line);
+    }
+
+    if let Some(file) = &logger.file {
+        if level <= logger.level.max(Level::Debug) {
+            if let Ok(mut file) = file.lock() {
+                let _ = writeln!(file, "{}", line);
+            }
+        }
+    }
+}
+
+pub fn debug(message: &str) {
+    log(Level::Debug, message);
+}
+
+pub fn trace(message: &str) {
+    log(Level::Trace, message);
+}
+
+// 執行外部指令並記錄參數、工作目錄、耗時與結束狀態
+pub fn output(cmd: &mut Command) -> io::Result<Output> {
+    let started = Instant::now();
+    let result = cmd.output();
+
+    if enabled(Level::Debug) {
+        match &result {
+            Ok(output) => {
+                log_command(cmd, &output.status, started);
+                if !output.status.success() {
+                    trace(&format!("  stderr: {}", String::from_utf8_lossy(&output.stderr).trim()));
+                }
+                trace(&format!("  stdout: {} bytes", output.stdout.len()));
+            }
+            Err(e) => debug(&format!("{} failed to start: {}", describe(cmd), e)),
+        }
+    }
+
+    result
+}
+
+pub fn status(cmd: &mut Command) -> io::Result<ExitStatus> {
+    let started = Instant::now();
+    let result = cmd.status();
+
+    if enabled(Level::Debug) {
+        match &result {
+            Ok(status) => log_command(cmd, status, started),
+            Err(e) => debug(&format!("{} failed to start: {}", describe(cmd), e)),
+        }
+    }
+
+    result
+}
+
+// 記錄 git2 (libgit2) 的操作與耗時
+pub fn git2<T>(operation: &str, f: impl FnOnce() -> T) -> T {
+    let started = Instant::now();
+    let result = f();
+    debug(&format!("git2 {} ({} ms)", operation, started.elapsed().as_millis()));
+    result
+}
+
+fn log_command(cmd: &Command, status: &ExitStatus, started: Instant) {
+    let exit = match status.code() {
+        Some(code) => format!("exit {}", code),
+        None => "terminated by signal".to_string(),
+    };
+    debug(&format!("{} -> {} ({} ms)", describe(cmd), exit, started.elapsed().as_millis()));
+}
+
+fn describe(cmd: &Command) -> String {
+    let mut parts = vec![cmd.get_program().to_string_lossy().to_string()];
+    parts.extend(cmd.get_args().map(|arg| {
+        let arg = arg.to_string_lossy();
+        if arg.contains(' ') { format!("'{}'", arg) } else { arg.to_string() }
+    }));
+
+    let cwd = cmd.get_current_dir()
+        .map(|dir| dir.display().to_string())
+        .unwrap_or_else(|| ".".to_string());
+
+    format!("{} [cwd: {}]", parts.join(" "), cwd)
+}
\ No newline at end of file
@@ -1,3 +1,9 @@
 pub mod paths;
 pub mod validation;
-pub mod system;
\ No newline at end of file
+pub mod system;
+pub mod time;
+pub mod logger;
+pub mod dry_run;
+pub mod archive;
+pub mod naming;
+pub mod tmux;
\ No newline at end of file
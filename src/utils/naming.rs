@@ -0,0 +1,221 @@
+use regex::Regex;
+use crate::config::NamingConfig;
+use crate::error::{WtError, WtResult};
+use crate::utils::validation::validate_branch_name;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Field {
+    Type,
+    Ticket,
+    Summary,
+}
+
+impl Field {
+    fn placeholder(&self) -> &'static str {
+        match self {
+            Field::Type => "{type}",
+            Field::Ticket => "{ticket}",
+            Field::Summary => "{summary}",
+        }
+    }
+}
+
+pub fn uses_field(template: &str, field: Field) -> bool {
+    template.contains(field.placeholder())
+}
+
+// 將自由文字轉為適合分支名稱的 slug，例如 "Fix login bug!" -> "fix-login-bug"
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    for c in text.chars().flat_map(|c| c.to_lowercase()) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+pub fn validate_type(config: &NamingConfig, kind: &str) -> WtResult<()> {
+    if kind.trim().is_empty() {
+        return Err(WtError::ValidationError("Type cannot be empty".to_string()));
+    }
+    if !config.types.is_empty() && !config.types.iter().any(|t| t == kind) {
+        return Err(WtError::ValidationError(format!(
+            "Unknown type '{}'; expected one of: {}", kind, config.types.join(", "))));
+    }
+    Ok(())
+}
+
+pub fn validate_ticket(config: &NamingConfig, ticket: &str) -> WtResult<()> {
+    let pattern = Regex::new(&format!("^(?:{})$", config.ticket_pattern))
+        .map_err(|e| WtError::ValidationError(format!("Invalid naming.ticket_pattern: {}", e)))?;
+    if !pattern.is_match(ticket) {
+        return Err(WtError::ValidationError(format!(
+            "Ticket '{}' does not match the pattern '{}'", ticket, config.ticket_pattern)));
+    }
+    Ok(())
+}
+
+// 依模板組出分支名稱；模板中出現的欄位都是必填
+pub fn build_branch_name(config: &NamingConfig, template: &str, kind: Option<&str>, ticket: Option<&str>, summary: Option<&str>) -> WtResult<String> {
+    let missing = |field: &str, hint: &str| WtError::ValidationError(format!("The naming template requires a {} ({})", field, hint));
+
+    let mut name = template.to_string();
+    if uses_field(template, Field::Type) {
+        let kind = kind.ok_or_else(|| missing("type", "--type <TYPE>"))?;
+        validate_type(config, kind)?;
+        name = name.replace(Field::Type.placeholder(), kind);
+    }
+    if uses_field(template, Field::Ticket) {
+        let ticket = ticket.ok_or_else(|| missing("ticket", "--ticket <ID>"))?;
+        validate_ticket(config, ticket)?;
+        name = name.replace(Field::Ticket.placeholder(), ticket);
+    }
+    if config.lowercase {
+        name = name.to_lowercase();
+    }
+
+    if uses_field(template, Field::Summary) {
+        let slug = slugify(summary.ok_or_else(|| missing("summary", "pass it as the branch argument"))?);
+        if slug.is_empty() {
+            return Err(WtError::ValidationError("Summary must contain letters or digits".to_string()));
+        }
+        name = fill_summary(&name, &slug, config.max_length);
+    }
+
+    check_branch_name(&name, config)?;
+    Ok(name)
+}
+
+// 超過長度上限時，從結尾逐字移除 summary 直到符合
+fn fill_summary(name: &str, slug: &str, max_length: Option<usize>) -> String {
+    let mut words: Vec<&str> = slug.split('-').collect();
+    loop {
+        let filled = name.replace(Field::Summary.placeholder(), &words.join("-"));
+        let fits = max_length.is_none_or(|max| filled.chars().count() <= max);
+        if fits || words.len() == 1 {
+            return filled;
+        }
+        words.pop();
+    }
+}
+
+// git 的命名規則之外，再套用 .wt.toml [naming] 中的前綴、正規表示式、禁用字詞、長度與大小寫規則
+pub fn check_branch_name(name: &str, config: &NamingConfig) -> WtResult<()> {
+    validate_branch_name(name)?;
+
+    if !config.prefixes.is_empty() && !config.prefixes.iter().any(|prefix| name.starts_with(prefix.as_str())) {
+        return Err(WtError::ValidationError(format!(
+            "Branch name must start with one of: {}", config.prefixes.join(", "))));
+    }
+
+    if !config.patterns.is_empty() {
+        let mut matched = false;
+        for pattern in &config.patterns {
+            let regex = Regex::new(pattern)
+                .map_err(|e| WtError::ValidationError(format!("Invalid naming.patterns entry '{}': {}", pattern, e)))?;
+            matched |= regex.is_match(name);
+        }
+        if !matched {
+            return Err(WtError::ValidationError(format!(
+                "Branch name must match one of the patterns: {}", config.patterns.join(", "))));
+        }
+    }
+
+    let words: Vec<String> = name.split(['/', '-', '_', '.']).map(|word| word.to_lowercase()).collect();
+    if let Some(word) = config.forbidden_words.iter().find(|forbidden| words.contains(&forbidden.to_lowercase())) {
+        return Err(WtError::ValidationError(format!("Branch name cannot contain the word '{}'", word)));
+    }
+
+    if let Some(max) = config.max_length {
+        let length = name.chars().count();
+        if length > max {
+            return Err(WtError::ValidationError(format!(
+                "Branch name is {} characters long; the maximum is {}", length, max)));
+        }
+    }
+
+    if config.lowercase && name.chars().any(|c| c.is_uppercase()) {
+        return Err(WtError::ValidationError("Branch name must be lowercase".to_string()));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEMPLATE: &str = "{type}/{ticket}-{summary}";
+
+    #[test]
+    fn builds_names_from_the_template() {
+        let config = NamingConfig::default();
+        let name = build_branch_name(&config, TEMPLATE, Some("feat"), Some("ABC-123"), Some("Fix login bug!")).unwrap();
+        assert_eq!(name, "feat/ABC-123-fix-login-bug");
+    }
+
+    #[test]
+    fn requires_every_field_in_the_template() {
+        let config = NamingConfig::default();
+        assert!(build_branch_name(&config, TEMPLATE, None, Some("ABC-123"), Some("x")).is_err());
+        assert!(build_branch_name(&config, TEMPLATE, Some("feat"), None, Some("x")).is_err());
+        assert!(build_branch_name(&config, TEMPLATE, Some("feat"), Some("ABC-123"), None).is_err());
+        assert!(build_branch_name(&config, TEMPLATE, Some("feat"), Some("ABC-123"), Some("!!")).is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_types_and_malformed_tickets() {
+        let config = NamingConfig::default();
+        assert!(build_branch_name(&config, TEMPLATE, Some("feature"), Some("ABC-123"), Some("x")).is_err());
+        assert!(build_branch_name(&config, TEMPLATE, Some("feat"), Some("abc-123"), Some("x")).is_err());
+    }
+
+    #[test]
+    fn trims_the_summary_to_the_maximum_length() {
+        let config = NamingConfig { max_length: Some(24), ..NamingConfig::default() };
+        let name = build_branch_name(&config, TEMPLATE, Some("fix"), Some("ABC-1"), Some("one two three four")).unwrap();
+        assert_eq!(name, "fix/ABC-1-one-two-three");
+    }
+
+    #[test]
+    fn lowercases_generated_names() {
+        let config = NamingConfig { lowercase: true, ..NamingConfig::default() };
+        let name = build_branch_name(&config, TEMPLATE, Some("feat"), Some("ABC-123"), Some("Login")).unwrap();
+        assert_eq!(name, "feat/abc-123-login");
+    }
+
+    #[test]
+    fn checks_prefixes_and_patterns() {
+        let config = NamingConfig {
+            prefixes: vec!["feat/".to_string(), "fix/".to_string()],
+            patterns: vec!["^[a-z]+/[A-Z]+-[0-9]+-".to_string()],
+            ..NamingConfig::default()
+        };
+        assert!(check_branch_name("feat/ABC-1-login", &config).is_ok());
+        assert!(check_branch_name("chore/ABC-1-login", &config).is_err());
+        assert!(check_branch_name("feat/login", &config).is_err());
+    }
+
+    #[test]
+    fn checks_forbidden_words_length_and_case() {
+        let config = NamingConfig {
+            forbidden_words: vec!["wip".to_string()],
+            max_length: Some(12),
+            lowercase: true,
+            ..NamingConfig::default()
+        };
+        assert!(check_branch_name("feat/login", &config).is_ok());
+        assert!(check_branch_name("feat/WIP-x", &config).is_err());
+        assert!(check_branch_name("feat/swiper", &config).is_ok());
+        assert!(check_branch_name("feat/too-long-name", &config).is_err());
+        assert!(check_branch_name("feat/Login", &config).is_err());
+    }
+
+    #[test]
+    fn applies_git_rules_first() {
+        assert!(check_branch_name("feat/a..b", &NamingConfig::default()).is_err());
+    }
+}
\ No newline at end of file
@@ -1,26 +1,17 @@
 use anyhow::Result;
 use std::path::{Path, PathBuf};
-use std::env;
 use std::fs;
 
-pub fn generate_worktree_path(project_name: &str, branch_name: &str) -> Result<PathBuf> {
-    // 路徑格式：../<project_name>-worktree/<project_name>-<branch_name>-worktree
-    let current_dir = env::current_dir()?;
-    let parent_dir = current_dir.parent()
-        .ok_or_else(|| anyhow::anyhow!("Cannot find parent directory"))?;
-    
-    let clean_branch = clean_branch_name(branch_name);
-    let worktree_parent = parent_dir.join(format!("{}-worktree", project_name));
-    let worktree_path = worktree_parent.join(format!("{}-{}-worktree", project_name, clean_branch));
-    
-    Ok(worktree_path)
+pub fn generate_worktree_path(root: &Path, template: &str, project_name: &str, branch_name: &str) -> PathBuf {
+    normalize_path(&root.join(expand_path_template(template, project_name, branch_name)))
 }
 
-pub fn ensure_parent_directory(path: &Path) -> Result<()> {
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)?;
-    }
-    Ok(())
+// 預設格式：../<project_name>-worktree/<project_name>-<branch_name>-worktree
+pub fn expand_path_template(template: &str, project_name: &str, branch_name: &str) -> String {
+    let clean_branch = clean_branch_name(branch_name);
+    template
+        .replace("{project}", project_name)
+        .replace("{branch}", &clean_branch)
 }
 
 pub fn normalize_path(path: &Path) -> PathBuf {
@@ -45,16 +36,87 @@ pub fn normalize_path(path: &Path) -> PathBuf {
     components.iter().collect()
 }
 
+// 目錄已存在，或已登記為 worktree（即使目錄已被刪除）
+pub fn is_path_taken(path: &Path, registered: &[PathBuf]) -> bool {
+    let canonical = |p: &Path| p.canonicalize().unwrap_or_else(|_| normalize_path(p));
+    path.exists() || registered.iter().any(|other| canonical(other) == canonical(path))
+}
+
+// 在名稱後加上 -2、-3… 直到找到沒被使用的路徑
+pub fn suggest_free_path(path: &Path, registered: &[PathBuf]) -> PathBuf {
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    (2..)
+        .map(|n| path.with_file_name(format!("{}-{}", name, n)))
+        .find(|candidate| !is_path_taken(candidate, registered))
+        .expect("an unused suffix always exists")
+}
+
 pub fn clean_branch_name(branch_name: &str) -> String {
     branch_name
-        .replace(' ', "-")
-        .replace('/', "-")
-        .replace('\\', "-")
-        .replace(':', "-")
-        .replace('?', "")
-        .replace('*', "")
-        .replace('<', "")
-        .replace('>', "")
-        .replace('|', "")
-        .replace('"', "")
+        .replace([' ', '/', '\\', ':'], "-")
+        .replace(['?', '*', '<', '>', '|', '"'], "")
+}
+
+// 將目錄中除了 .git 以外的所有項目搬到另一個目錄
+pub fn move_contents(from: &Path, to: &Path) -> Result<()> {
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        if entry.file_name() == ".git" {
+            continue;
+        }
+        fs::rename(entry.path(), to.join(entry.file_name()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("wt-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn generates_paths_from_the_template() {
+        let path = generate_worktree_path(Path::new("/work/app"), "../{project}-worktree/{project}-{branch}-worktree", "app", "feat/login");
+        assert_eq!(path, PathBuf::from("/work/app-worktree/app-feat-login-worktree"));
+        assert_eq!(generate_worktree_path(Path::new("/work/app"), "{branch}", "app", "fix/x"), PathBuf::from("/work/app/fix-x"));
+    }
+
+    #[test]
+    fn normalizes_dot_components() {
+        assert_eq!(normalize_path(Path::new("/a/./b/../c")), PathBuf::from("/a/c"));
+    }
+
+    #[test]
+    fn cleans_characters_that_do_not_belong_in_directory_names() {
+        assert_eq!(clean_branch_name("feat/a b:c\\d"), "feat-a-b-c-d");
+        assert_eq!(clean_branch_name("what?*<>|\"now"), "whatnow");
+    }
+
+    #[test]
+    fn suggests_the_next_free_suffix() {
+        let dir = temp_dir("suggest");
+        let path = dir.join("app-feat-worktree");
+        fs::create_dir(&path).unwrap();
+        assert_eq!(suggest_free_path(&path, &[]), dir.join("app-feat-worktree-2"));
+
+        fs::create_dir(dir.join("app-feat-worktree-2")).unwrap();
+        assert_eq!(suggest_free_path(&path, &[]), dir.join("app-feat-worktree-3"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn skips_registered_worktrees_whose_directory_is_gone() {
+        let dir = temp_dir("registered");
+        let path = dir.join("app-feat-worktree");
+        let registered = vec![path.clone(), dir.join("app-feat-worktree-2")];
+        assert!(is_path_taken(&path, &registered));
+        assert_eq!(suggest_free_path(&path, &registered), dir.join("app-feat-worktree-3"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
\ No newline at end of file
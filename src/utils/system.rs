@@ -1,15 +1,18 @@
 use anyhow::{Result, anyhow};
+use std::collections::BTreeMap;
 use std::path::Path;
-use std::process::Command;
+use std::process::{Command, Stdio};
+use crate::ui::display::Display;
+use crate::utils::dry_run::{self, PlannedAction};
+use crate::utils::logger;
 
 pub fn open_vscode(path: &Path) -> Result<()> {
     if !check_vscode_available() {
         return Err(anyhow!("VSCode is not available. Make sure 'code' command is in your PATH."));
     }
 
-    let output = Command::new("code")
-        .arg(path)
-        .output()?;
+    let output = logger::output(Command::new("code")
+        .arg(path))?;
 
     if !output.status.success() {
         let error_message = String::from_utf8_lossy(&output.stderr);
@@ -20,9 +23,56 @@ pub fn open_vscode(path: &Path) -> Result<()> {
 }
 
 pub fn check_vscode_available() -> bool {
-    Command::new("code")
-        .arg("--version")
-        .output()
+    logger::output(Command::new("code")
+        .arg("--version"))
         .map(|output| output.status.success())
         .unwrap_or(false)
+}
+
+pub fn run_hook(command: &str, cwd: &Path, envs: &[(&str, String)]) -> Result<()> {
+    let mut cmd = if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C").arg(command);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(command);
+        cmd
+    };
+
+    // hook 的輸出直接顯示給使用者；JSON 模式下改到 stderr，保持 stdout 只有結果文件
+    if !Display::is_human() {
+        cmd.stdout(Stdio::from(std::io::stderr()));
+    }
+    let status = logger::status(cmd.current_dir(cwd).envs(envs.iter().map(|(k, v)| (k, v))))?;
+
+    if !status.success() {
+        return Err(anyhow!("Hook '{}' failed with {}", command, status));
+    }
+
+    Ok(())
+}
+
+// 除了 WT_WORKTREE_PATH 與 WT_BRANCH，也帶入 [env] 為此 worktree 分配的變數
+pub fn run_post_add_hooks(hooks: &[String], worktree_path: &Path, branch: &str, vars: &BTreeMap<String, String>) -> Result<()> {
+    let mut envs = vec![
+        ("WT_WORKTREE_PATH", worktree_path.to_string_lossy().to_string()),
+        ("WT_BRANCH", branch.to_string()),
+    ];
+    envs.extend(vars.iter().map(|(name, value)| (name.as_str(), value.clone())));
+
+    for hook in hooks {
+        if dry_run::is_enabled() {
+            dry_run::record(PlannedAction::RunHook {
+                command: hook.clone(),
+                cwd: worktree_path.to_path_buf(),
+            });
+            continue;
+        }
+
+        Display::show_info(&format!("Running hook: {}", hook));
+        run_hook(hook, worktree_path, &envs)?;
+    }
+
+    Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,150 @@
+use anyhow::{Result, anyhow};
+use is_terminal::IsTerminal;
+use std::path::Path;
+use std::process::Command;
+use crate::config::{TmuxConfig, TmuxMode};
+use crate::ui::display::Display;
+use crate::utils::dry_run::{self, PlannedAction};
+use crate::utils::logger;
+use crate::utils::paths::clean_branch_name;
+
+// tmux 不允許名稱中出現 '.' 與 ':'
+pub fn session_name(project: &str, worktree: &str) -> String {
+    format!("{}-{}", project, clean_branch_name(worktree)).replace('.', "_")
+}
+
+pub fn is_available() -> bool {
+    logger::output(Command::new("tmux").arg("-V"))
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+fn inside_tmux() -> bool {
+    std::env::var_os("TMUX").is_some()
+}
+
+fn tmux(args: &[&str]) -> Result<String> {
+    let output = logger::output(Command::new("tmux").args(args))?;
+    if !output.status.success() {
+        return Err(anyhow!("tmux {} failed: {}", args.first().unwrap_or(&""), String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+// 已存在的 session 或 window 的 id，找不到時為 None
+fn find(mode: TmuxMode, name: &str) -> Option<String> {
+    match mode {
+        TmuxMode::Session => tmux(&["has-session", "-t", &format!("={}", name)]).ok()
+            .and_then(|_| tmux(&["display-message", "-p", "-t", &format!("={}", name), "#{session_id}"]).ok()),
+        TmuxMode::Window => tmux(&["list-windows", "-a", "-F", "#{window_id} #{window_name}"]).ok()?
+            .lines()
+            .find_map(|line| line.split_once(' ').filter(|(_, window)| *window == name).map(|(id, _)| id.to_string())),
+    }
+}
+
+// 開啟 worktree 對應的 session 或 window；已存在時直接切換過去。回傳是否為新建立
+pub fn open(config: &TmuxConfig, name: &str, path: &Path) -> Result<bool> {
+    if !is_available() {
+        return Err(anyhow!("tmux is not available. Make sure 'tmux' is in your PATH."));
+    }
+    if config.mode == TmuxMode::Window && !inside_tmux() {
+        return Err(anyhow!("tmux window mode only works inside a tmux session; set [open.tmux] mode = \"session\" or start tmux first"));
+    }
+
+    let existing = find(config.mode, name);
+    let created = existing.is_none();
+    let target = match existing {
+        Some(id) => id,
+        None => create(config, name, path)?,
+    };
+
+    attach(config.mode, name, &target)?;
+    Ok(created)
+}
+
+fn create(config: &TmuxConfig, name: &str, path: &Path) -> Result<String> {
+    let cwd = path.to_string_lossy();
+    // 以 pane id 指定目標，避免名稱中的字元被 tmux 解讀
+    let first = match config.mode {
+        TmuxMode::Session => tmux(&["new-session", "-d", "-P", "-F", "#{pane_id}", "-s", name, "-c", &cwd])?,
+        TmuxMode::Window => tmux(&["new-window", "-d", "-P", "-F", "#{pane_id}", "-n", name, "-c", &cwd])?,
+    };
+
+    // 第一個 pane 已存在，其餘依序分割出來；指令以按鍵送出，結束後仍保留 shell
+    for (index, command) in config.panes.iter().enumerate() {
+        let pane = if index == 0 {
+            first.clone()
+        } else {
+            tmux(&["split-window", "-d", "-P", "-F", "#{pane_id}", "-t", &first, "-c", &cwd])?
+        };
+        if !command.trim().is_empty() {
+            tmux(&["send-keys", "-t", &pane, command, "Enter"])?;
+        }
+    }
+    if let Some(layout) = &config.layout {
+        tmux(&["select-layout", "-t", &first, layout])?;
+    }
+
+    Ok(first)
+}
+
+fn attach(mode: TmuxMode, name: &str, target: &str) -> Result<()> {
+    if inside_tmux() {
+        match mode {
+            TmuxMode::Session => tmux(&["switch-client", "-t", target])?,
+            TmuxMode::Window => tmux(&["select-window", "-t", target])?,
+        };
+        return Ok(());
+    }
+
+    // 沒有終端機（例如 JSON 模式或腳本中）時只建立，不接上
+    if !Display::is_human() || !std::io::stdout().is_terminal() {
+        Display::show_info(&format!("Run 'tmux attach -t {}' to attach to it", name));
+        return Ok(());
+    }
+
+    let status = logger::status(Command::new("tmux").args(["attach-session", "-t", target]))?;
+    if !status.success() {
+        return Err(anyhow!("Failed to attach to tmux session '{}'", name));
+    }
+    Ok(())
+}
+
+// 移除 worktree 時關閉對應的 session 或 window；tmux 未安裝或沒有執行時略過
+pub fn kill(config: &TmuxConfig, name: &str) -> Result<()> {
+    if !config.kill_on_remove || !is_available() {
+        return Ok(());
+    }
+    let Some(target) = find(config.mode, name) else {
+        return Ok(());
+    };
+    // 不關閉 wt 自己所在的 session 或 window
+    if inside_tmux() {
+        let format = match config.mode {
+            TmuxMode::Session => "#{session_id}",
+            TmuxMode::Window => "#{window_id}",
+        };
+        if tmux(&["display-message", "-p", format]).ok().as_deref() == Some(target.as_str()) {
+            Display::show_warning(&format!("Not closing tmux {} '{}' because wt is running inside it", mode_name(config.mode), name));
+            return Ok(());
+        }
+    }
+
+    if dry_run::is_enabled() {
+        dry_run::record(PlannedAction::KillTmux { name: name.to_string() });
+        return Ok(());
+    }
+
+    match config.mode {
+        TmuxMode::Session => tmux(&["kill-session", "-t", &target])?,
+        TmuxMode::Window => tmux(&["kill-window", "-t", &target])?,
+    };
+    Ok(())
+}
+
+pub fn mode_name(mode: TmuxMode) -> &'static str {
+    match mode {
+        TmuxMode::Session => "session",
+        TmuxMode::Window => "window",
+    }
+}
\ No newline at end of file
@@ -1,5 +1,7 @@
+use std::path::{Component, Path, PathBuf};
 use crate::error::{WtError, WtResult};
 
+// 規則與 git check-ref-format --branch 相同；常見錯誤先給出具體訊息，最後交由 git2 判斷
 pub fn validate_branch_name(name: &str) -> WtResult<()> {
     if name.trim().is_empty() {
         return Err(WtError::ValidationError("Branch name cannot be empty".to_string()));
@@ -14,51 +16,95 @@ pub fn validate_branch_name(name: &str) -> WtResult<()> {
         return Err(WtError::ValidationError("Branch name cannot contain spaces".to_string()));
     }
 
-    let trimmed = name;
+    if name.starts_with('-') {
+        return Err(WtError::ValidationError("Branch name cannot start with '-'".to_string()));
+    }
 
     // Git branch naming rules
-    if trimmed.starts_with('/') || trimmed.ends_with('/') {
+    if name.starts_with('/') || name.ends_with('/') {
         return Err(WtError::ValidationError("Branch name cannot start or end with '/'".to_string()));
     }
 
-    if trimmed.contains("//") {
+    if name.contains("//") {
         return Err(WtError::ValidationError("Branch name cannot contain consecutive slashes".to_string()));
     }
 
-    if trimmed.starts_with('.') || trimmed.ends_with('.') {
-        return Err(WtError::ValidationError("Branch name cannot start or end with '.'".to_string()));
+    if name.contains("..") {
+        return Err(WtError::ValidationError("Branch name cannot contain '..'".to_string()));
+    }
+
+    if name.contains("@{") {
+        return Err(WtError::ValidationError("Branch name cannot contain '@{'".to_string()));
+    }
+
+    if name == "@" {
+        return Err(WtError::ValidationError("'@' is not a valid branch name".to_string()));
+    }
+
+    if name.ends_with('.') {
+        return Err(WtError::ValidationError("Branch name cannot end with '.'".to_string()));
     }
 
-    if trimmed.ends_with(".lock") {
-        return Err(WtError::ValidationError("Branch name cannot end with '.lock'".to_string()));
+    // 每個以 '/' 分隔的部分都不能以 '.' 開頭或以 '.lock' 結尾
+    for component in name.split('/') {
+        if component.starts_with('.') {
+            return Err(WtError::ValidationError(format!("'{}' cannot start with '.'", component)));
+        }
+        if component.ends_with(".lock") {
+            return Err(WtError::ValidationError(format!("'{}' cannot end with '.lock'", component)));
+        }
     }
 
     // Check for invalid characters
     let invalid_chars = ['~', '^', ':', '?', '*', '[', '\\', ' ', '\t', '\n'];
     for &ch in &invalid_chars {
-        if trimmed.contains(ch) {
-            return Err(WtError::ValidationError(format!("Branch name cannot contain '{}'", ch)));
+        if name.contains(ch) {
+            return Err(WtError::ValidationError(format!("Branch name cannot contain '{}'", ch.escape_default())));
         }
     }
 
     // Check for ASCII control characters
-    if trimmed.chars().any(|c| c.is_control()) {
+    if name.chars().any(|c| c.is_control()) {
         return Err(WtError::ValidationError("Branch name cannot contain control characters".to_string()));
     }
 
     // Check against reserved names
-    if trimmed == "HEAD" {
+    if name == "HEAD" {
         return Err(WtError::ValidationError("'HEAD' is a reserved name".to_string()));
     }
 
-    if trimmed.starts_with("refs/") {
+    if name.starts_with("refs/") {
         return Err(WtError::ValidationError("Branch name cannot start with 'refs/'".to_string()));
     }
 
+    if !git2::Reference::is_valid_name(&format!("refs/heads/{}", name)) {
+        return Err(WtError::ValidationError(format!("'{}' is not a valid git branch name", name)));
+    }
+
     Ok(())
 }
 
+// 只檢查由 wt 產生的部分：路徑模板展開後的相對路徑，略過 . 與 .. 這類導覽用的部分
+pub fn validate_template_path(relative: &str) -> WtResult<()> {
+    let generated: PathBuf = Path::new(relative).components()
+        .filter(|component| matches!(component, Component::Normal(_)))
+        .collect();
+    validate_path(&generated.to_string_lossy())
+}
+
+// 使用者指定的路徑只檢查要建立的最後一層目錄，上層目錄本來就存在或由使用者決定
+pub fn validate_path_name(path: &Path) -> WtResult<()> {
+    let name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    validate_path(&name)
+}
+
 pub fn validate_path(path: &str) -> WtResult<()> {
+    // Windows 的磁碟代號（例如 C:）不算在無效字元內
+    let path = match path.as_bytes() {
+        [drive, b':', ..] if drive.is_ascii_alphabetic() => &path[2..],
+        _ => path,
+    };
+
     if path.trim().is_empty() {
         return Err(WtError::ValidationError("Path cannot be empty".to_string()));
     }
@@ -114,4 +160,42 @@ pub fn validate_path(path: &str) -> WtResult<()> {
     }
 
     Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_common_branch_names() {
+        for name in ["main", "feature/login", "fix/ABC-123-crash", "release-1.2", "user/eno/wip_2"] {
+            assert!(validate_branch_name(name).is_ok(), "{} should be valid", name);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_branch_names() {
+        let invalid = [
+            "", " main", "main ", "has space", "-flag", "/lead", "trail/", "a//b", "a..b",
+            "a@{1}", "@", "end.", "feat/.hidden", "feat/x.lock", "a~b", "a^b", "a:b",
+            "a?b", "a*b", "a[b", "a\\b", "a\tb", "a\u{7}b", "HEAD", "refs/heads/x",
+        ];
+        for name in invalid {
+            assert!(validate_branch_name(name).is_err(), "{:?} should be invalid", name);
+        }
+    }
+
+    #[test]
+    fn template_paths_skip_parent_directories() {
+        assert!(validate_template_path("../proj-worktree/proj-feat-login-worktree").is_ok());
+        assert!(validate_template_path("./feat").is_ok());
+        assert!(validate_template_path("../proj-worktree/a|b").is_err());
+    }
+
+    #[test]
+    fn path_names_only_check_the_last_component() {
+        assert!(validate_path_name(Path::new("/tmp/a:b/worktree")).is_ok());
+        assert!(validate_path_name(Path::new("/tmp/worktree?")).is_err());
+        assert!(validate_path_name(Path::new("/tmp/CON")).is_err());
+    }
 }
\ No newline at end of file
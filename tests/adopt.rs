@@ -0,0 +1,176 @@
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+// 每個測試使用獨立的暫存目錄，內含 master 與 feature 兩個分支的倉庫
+struct Sandbox {
+    dir: PathBuf,
+    repo: PathBuf,
+}
+
+impl Sandbox {
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("wt-adopt-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let dir = dir.canonicalize().unwrap();
+
+        let repo = dir.join("app");
+        fs::create_dir(&repo).unwrap();
+        git(&repo, &["init", "-q", "-b", "master"]);
+        fs::write(repo.join("README.md"), "hello\n").unwrap();
+        git(&repo, &["add", "."]);
+        git(&repo, &["commit", "-q", "-m", "initial"]);
+        git(&repo, &["branch", "feature"]);
+
+        Self { dir, repo }
+    }
+
+    // 以 file:// 另外 clone 一份倉庫並切到指定分支
+    fn clone(&self, name: &str, branch: &str) -> PathBuf {
+        let clone = self.dir.join(name);
+        git(&self.dir, &["clone", "-q", &format!("file://{}", self.repo.display()), clone.to_str().unwrap()]);
+        git(&clone, &["checkout", "-q", "-B", branch]);
+        clone
+    }
+
+    fn run(&self, args: &[&str]) -> (Output, Value) {
+        let mut full = vec!["--output", "json"];
+        full.extend(args);
+        let output = Command::new(env!("CARGO_BIN_EXE_wt"))
+            .args(&full)
+            .current_dir(&self.repo)
+            .envs(identity())
+            .env_remove("WT_REPO")
+            .output()
+            .unwrap();
+        let value: Value = serde_json::from_slice(&output.stdout).unwrap();
+        (output, value)
+    }
+
+    fn wt(&self, args: &[&str]) -> Value {
+        let (output, value) = self.run(args);
+        assert!(output.status.success(), "wt {:?} failed: {}", args, String::from_utf8_lossy(&output.stderr));
+        assert_eq!(value["ok"], true);
+        value["result"].clone()
+    }
+
+    // 預期失敗，回傳錯誤訊息
+    fn wt_error(&self, args: &[&str]) -> String {
+        let (output, value) = self.run(args);
+        assert!(!output.status.success(), "wt {:?} unexpectedly succeeded", args);
+        assert_eq!(value["ok"], false);
+        value["error"]["message"].as_str().unwrap().to_string()
+    }
+}
+
+impl Drop for Sandbox {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+fn identity() -> [(&'static str, &'static str); 4] {
+    [
+        ("GIT_AUTHOR_NAME", "wt"),
+        ("GIT_AUTHOR_EMAIL", "wt@example.com"),
+        ("GIT_COMMITTER_NAME", "wt"),
+        ("GIT_COMMITTER_EMAIL", "wt@example.com"),
+    ]
+}
+
+fn git(cwd: &Path, args: &[&str]) -> String {
+    let output = Command::new("git").args(args).current_dir(cwd).envs(identity()).output().unwrap();
+    assert!(output.status.success(), "git {:?} failed: {}", args, String::from_utf8_lossy(&output.stderr));
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+fn commit(cwd: &Path, file: &str, content: &str) -> String {
+    fs::write(cwd.join(file), content).unwrap();
+    git(cwd, &["add", file]);
+    git(cwd, &["commit", "-q", "-m", file]);
+    git(cwd, &["rev-parse", "HEAD"])
+}
+
+#[test]
+fn adopts_a_clone_with_local_commits_and_uncommitted_files() {
+    let sandbox = Sandbox::new("clone");
+    let clone = sandbox.clone("other", "topic");
+    let local = commit(&clone, "local.txt", "local\n");
+    fs::write(clone.join("README.md"), "changed\n").unwrap();
+    fs::write(clone.join("scratch.txt"), "untracked\n").unwrap();
+
+    let result = sandbox.wt(&["adopt", clone.to_str().unwrap()]);
+    let worktree = sandbox.dir.join("app-worktree").join("app-topic-worktree");
+    assert_eq!(PathBuf::from(result["path"].as_str().unwrap()), worktree);
+    assert_eq!(result["branch"], "topic");
+    assert_eq!(result["mode"], "new_branch");
+    assert_eq!(result["retired_clone"], Value::Null);
+
+    // 本地的 commit 已取回倉庫，未提交的變更原樣保留
+    assert_eq!(git(&sandbox.repo, &["rev-parse", "refs/heads/topic"]), local);
+    assert_eq!(git(&worktree, &["rev-parse", "--abbrev-ref", "HEAD"]), "topic");
+    assert_eq!(fs::read_to_string(worktree.join("README.md")).unwrap(), "changed\n");
+    assert_eq!(git(&worktree, &["status", "--porcelain"]), "M README.md\n?? scratch.txt");
+    assert!(!clone.exists());
+
+    // 換了位置的 clone 記錄為移動，wt undo 把 worktree 移回原處
+    let journal = sandbox.wt(&["undo", "--list"]);
+    let entry = journal["entries"].as_array().unwrap().last().unwrap().clone();
+    assert_eq!(entry["operation"], "move");
+    assert_eq!(PathBuf::from(entry["moved_from"].as_str().unwrap()), clone);
+
+    sandbox.wt(&["undo"]);
+    assert!(!worktree.exists());
+    assert_eq!(git(&clone, &["rev-parse", "--abbrev-ref", "HEAD"]), "topic");
+    assert_eq!(fs::read_to_string(clone.join("scratch.txt")).unwrap(), "untracked\n");
+}
+
+#[test]
+fn fast_forwards_an_existing_branch() {
+    let sandbox = Sandbox::new("fast-forward");
+    let clone = sandbox.clone("other", "feature");
+    let local = commit(&clone, "local.txt", "local\n");
+
+    let result = sandbox.wt(&["adopt", clone.to_str().unwrap()]);
+    assert_eq!(result["branch"], "feature");
+    assert_eq!(result["mode"], "existing_branch");
+    assert_eq!(git(&sandbox.repo, &["rev-parse", "refs/heads/feature"]), local);
+}
+
+#[test]
+fn refuses_a_clone_whose_branch_diverged() {
+    let sandbox = Sandbox::new("diverged");
+    let clone = sandbox.clone("other", "feature");
+    let theirs = commit(&clone, "theirs.txt", "theirs\n");
+
+    git(&sandbox.repo, &["checkout", "-q", "feature"]);
+    let ours = commit(&sandbox.repo, "ours.txt", "ours\n");
+    git(&sandbox.repo, &["checkout", "-q", "master"]);
+
+    let message = sandbox.wt_error(&["adopt", clone.to_str().unwrap()]);
+    assert!(message.contains("Branch 'feature' in this repository has diverged"), "{}", message);
+
+    // 分支與 clone 都保持原樣，也沒有建立 worktree
+    assert_eq!(git(&sandbox.repo, &["rev-parse", "refs/heads/feature"]), ours);
+    assert_eq!(git(&clone, &["rev-parse", "HEAD"]), theirs);
+    assert!(!sandbox.dir.join("app-worktree").exists());
+    assert!(!git(&sandbox.repo, &["worktree", "list"]).contains("[feature]"));
+    assert_eq!(git(&sandbox.repo, &["for-each-ref", "refs/wt"]), "");
+}
+
+#[test]
+fn refuses_a_clone_with_unrelated_history() {
+    let sandbox = Sandbox::new("unrelated");
+    let other = sandbox.dir.join("other");
+    fs::create_dir(&other).unwrap();
+    git(&other, &["init", "-q", "-b", "topic"]);
+    commit(&other, "unrelated.txt", "unrelated\n");
+
+    let message = sandbox.wt_error(&["adopt", other.to_str().unwrap()]);
+    assert!(message.contains("does not share history with this repository"), "{}", message);
+    assert!(other.join(".git").is_dir());
+    assert!(!sandbox.dir.join("app-worktree").exists());
+    assert_eq!(git(&sandbox.repo, &["for-each-ref", "refs/wt"]), "");
+}
\ No newline at end of file
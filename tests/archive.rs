@@ -0,0 +1,146 @@
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// 每個測試使用獨立的暫存目錄，內含 master 與 feature 兩個分支的倉庫，feature 已有 worktree
+struct Sandbox {
+    dir: PathBuf,
+    repo: PathBuf,
+    worktree: PathBuf,
+}
+
+impl Sandbox {
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("wt-archive-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let dir = dir.canonicalize().unwrap();
+
+        let repo = dir.join("app");
+        fs::create_dir(&repo).unwrap();
+        git(&repo, &["init", "-q", "-b", "master"]);
+        fs::write(repo.join("README.md"), "hello\n").unwrap();
+        fs::write(repo.join(".gitignore"), "build/\n").unwrap();
+        git(&repo, &["add", "."]);
+        git(&repo, &["commit", "-q", "-m", "initial"]);
+        git(&repo, &["branch", "feature"]);
+
+        let worktree = dir.join("feature");
+        git(&repo, &["worktree", "add", "-q", worktree.to_str().unwrap(), "feature"]);
+
+        // 一個 commit，再加上未提交、未追蹤與被忽略的檔案
+        fs::create_dir(worktree.join("src")).unwrap();
+        fs::write(worktree.join("src").join("lib.rs"), "pub fn feature() {}\n").unwrap();
+        git(&worktree, &["add", "."]);
+        git(&worktree, &["commit", "-q", "-m", "feature"]);
+        fs::write(worktree.join("README.md"), "changed\n").unwrap();
+        fs::write(worktree.join("todo.txt"), "untracked\n").unwrap();
+        fs::create_dir(worktree.join("build")).unwrap();
+        fs::write(worktree.join("build").join("out.bin"), [0u8, 1, 2, 255]).unwrap();
+
+        Self { dir, repo, worktree }
+    }
+
+    fn wt(&self, args: &[&str]) -> Value {
+        let mut full = vec!["--output", "json"];
+        full.extend(args);
+        let output = Command::new(env!("CARGO_BIN_EXE_wt"))
+            .args(&full)
+            .current_dir(&self.repo)
+            .envs(identity())
+            .env_remove("WT_REPO")
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "wt {:?} failed: {}", full, String::from_utf8_lossy(&output.stderr));
+
+        let value: Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(value["ok"], true);
+        value["result"].clone()
+    }
+
+    // 封存並移除 feature worktree，回傳封存結果
+    fn archive(&self, format: &str, file: &Path) -> Value {
+        let result = self.wt(&["archive", "feature", "--include-ignored", "--remove",
+            "--format", format, "--file", file.to_str().unwrap()]);
+        assert!(file.is_file());
+        assert!(!self.worktree.exists());
+        assert!(!git(&self.repo, &["worktree", "list"]).contains("[feature]"));
+        result
+    }
+}
+
+impl Drop for Sandbox {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+fn identity() -> [(&'static str, &'static str); 4] {
+    [
+        ("GIT_AUTHOR_NAME", "wt"),
+        ("GIT_AUTHOR_EMAIL", "wt@example.com"),
+        ("GIT_COMMITTER_NAME", "wt"),
+        ("GIT_COMMITTER_EMAIL", "wt@example.com"),
+    ]
+}
+
+fn git(cwd: &Path, args: &[&str]) -> String {
+    let output = Command::new("git").args(args).current_dir(cwd).envs(identity()).output().unwrap();
+    assert!(output.status.success(), "git {:?} failed: {}", args, String::from_utf8_lossy(&output.stderr));
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+// 解開後的 worktree 與封存前相同，且不留下 manifest
+fn assert_restored(worktree: &Path, commit: &str) {
+    assert_eq!(git(worktree, &["rev-parse", "--abbrev-ref", "HEAD"]), "feature");
+    assert_eq!(git(worktree, &["rev-parse", "HEAD"]), commit);
+    assert_eq!(fs::read_to_string(worktree.join("src").join("lib.rs")).unwrap(), "pub fn feature() {}\n");
+    assert_eq!(fs::read_to_string(worktree.join("README.md")).unwrap(), "changed\n");
+    assert_eq!(fs::read_to_string(worktree.join("todo.txt")).unwrap(), "untracked\n");
+    assert_eq!(fs::read(worktree.join("build").join("out.bin")).unwrap(), [0u8, 1, 2, 255]);
+    assert!(!worktree.join(".wt-archive.json").exists());
+    assert_eq!(git(worktree, &["status", "--porcelain"]), "M README.md\n?? todo.txt");
+}
+
+#[test]
+fn round_trips_a_tar_gz_archive() {
+    let sandbox = Sandbox::new("tar");
+    let commit = git(&sandbox.worktree, &["rev-parse", "HEAD"]);
+    let file = sandbox.dir.join("feature.tar.gz");
+
+    let result = sandbox.archive("tar.gz", &file);
+    assert_eq!(result["format"], "tar.gz");
+    assert_eq!(result["manifest"]["branch"], "feature");
+    assert_eq!(result["manifest"]["commit"], commit.as_str());
+    assert_eq!(result["manifest"]["base"], "master");
+    assert_eq!(result["manifest"]["include_ignored"], true);
+    assert_eq!(PathBuf::from(result["manifest"]["path"].as_str().unwrap()), sandbox.worktree);
+
+    // 分支仍在，解開到原本的路徑
+    let restored = sandbox.wt(&["unarchive", file.to_str().unwrap()]);
+    assert_eq!(restored["branch"], "feature");
+    assert_eq!(restored["commit"], commit.as_str());
+    assert_eq!(restored["mode"], "existing_branch");
+    assert_restored(&sandbox.worktree, &commit);
+}
+
+#[test]
+fn round_trips_a_zip_archive() {
+    let sandbox = Sandbox::new("zip");
+    let commit = git(&sandbox.worktree, &["rev-parse", "HEAD"]);
+    let file = sandbox.dir.join("feature.zip");
+
+    let result = sandbox.archive("zip", &file);
+    assert_eq!(result["format"], "zip");
+    assert_eq!(result["manifest"]["commit"], commit.as_str());
+
+    // 分支已被刪除時由封存的 commit 重新建立，並解開到另一個路徑
+    git(&sandbox.repo, &["branch", "-D", "feature"]);
+    let target = sandbox.dir.join("restored");
+    let restored = sandbox.wt(&["unarchive", file.to_str().unwrap(), "--path", target.to_str().unwrap()]);
+    assert_eq!(restored["branch"], "feature");
+    assert_eq!(restored["commit"], commit.as_str());
+    assert_eq!(restored["mode"], "new_branch");
+    assert_restored(&target, &commit);
+}
\ No newline at end of file
@@ -0,0 +1,147 @@
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+const FETCH_REFSPEC: &str = "+refs/heads/*:refs/remotes/origin/*";
+
+// 每個測試使用獨立的暫存目錄，內含來源倉庫與 clone 的目的地
+struct Sandbox {
+    dir: PathBuf,
+}
+
+impl Sandbox {
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("wt-clone-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        Self { dir: dir.canonicalize().unwrap() }
+    }
+
+    // 含 master 與 feature 兩個分支的來源倉庫
+    fn source(&self, files: &[(&str, &str)]) -> String {
+        let source = self.dir.join("source");
+        fs::create_dir(&source).unwrap();
+        git(&source, &["init", "-q", "-b", "master"]);
+        fs::write(source.join("README.md"), "hello\n").unwrap();
+        for (name, content) in files {
+            fs::write(source.join(name), content).unwrap();
+        }
+        git(&source, &["add", "."]);
+        git(&source, &["commit", "-q", "-m", "initial"]);
+        git(&source, &["branch", "feature"]);
+        format!("file://{}", source.display())
+    }
+
+    fn wt(&self, args: &[&str]) -> Output {
+        let output = Command::new(env!("CARGO_BIN_EXE_wt"))
+            .args(args)
+            .current_dir(&self.dir)
+            .envs(identity())
+            .env_remove("WT_REPO")
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "wt {:?} failed: {}", args, String::from_utf8_lossy(&output.stderr));
+        output
+    }
+
+    fn clone_json(&self, args: &[&str]) -> Value {
+        let mut full = vec!["--output", "json", "clone"];
+        full.extend(args);
+        let output = self.wt(&full);
+        let value: Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(value["ok"], true);
+        value["result"].clone()
+    }
+}
+
+impl Drop for Sandbox {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+fn identity() -> [(&'static str, &'static str); 4] {
+    [
+        ("GIT_AUTHOR_NAME", "wt"),
+        ("GIT_AUTHOR_EMAIL", "wt@example.com"),
+        ("GIT_COMMITTER_NAME", "wt"),
+        ("GIT_COMMITTER_EMAIL", "wt@example.com"),
+    ]
+}
+
+fn git(cwd: &Path, args: &[&str]) -> String {
+    let output = Command::new("git").args(args).current_dir(cwd).envs(identity()).output().unwrap();
+    assert!(output.status.success(), "git {:?} failed: {}", args, String::from_utf8_lossy(&output.stderr));
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+fn path(value: &Value) -> PathBuf {
+    PathBuf::from(value.as_str().unwrap())
+}
+
+#[test]
+fn clones_a_standard_repository() {
+    let sandbox = Sandbox::new("standard");
+    let url = sandbox.source(&[]);
+
+    let result = sandbox.clone_json(&[&url, "app"]);
+    let root = sandbox.dir.join("app");
+    assert_eq!(path(&result["root"]), root);
+    assert_eq!(path(&result["default_worktree"]), root);
+    assert_eq!(result["default_branch"], "master");
+
+    assert_eq!(git(&root, &["config", "--get-all", "remote.origin.fetch"]), FETCH_REFSPEC);
+    assert_eq!(git(&root, &["rev-parse", "--abbrev-ref", "HEAD"]), "master");
+    git(&root, &["rev-parse", "--verify", "refs/remotes/origin/feature"]);
+
+    // 起始設定檔寫在倉庫根目錄並排除在 git status 之外
+    let starter = fs::read_to_string(root.join(".wt.toml")).unwrap();
+    assert!(starter.starts_with("# wt configuration"));
+    assert_eq!(git(&root, &["status", "--porcelain"]), "");
+}
+
+#[test]
+fn clones_a_bare_hub() {
+    let sandbox = Sandbox::new("hub");
+    let url = sandbox.source(&[]);
+
+    let result = sandbox.clone_json(&["--bare-hub", &url, "hub"]);
+    let hub = sandbox.dir.join("hub");
+    let git_dir = hub.join(".bare");
+    assert_eq!(path(&result["root"]), hub);
+    assert_eq!(path(&result["default_worktree"]), hub.join("master"));
+
+    assert_eq!(fs::read_to_string(hub.join(".git")).unwrap().trim(), "gitdir: ./.bare");
+    assert_eq!(git(&git_dir, &["config", "core.bare"]), "true");
+    assert_eq!(git(&git_dir, &["config", "--get-all", "remote.origin.fetch"]), FETCH_REFSPEC);
+    git(&git_dir, &["rev-parse", "--verify", "refs/remotes/origin/feature"]);
+
+    // 預設分支的 worktree 與 .bare 並列，不在 git 目錄裡
+    let worktree = hub.join("master");
+    assert!(worktree.join("README.md").is_file());
+    assert_eq!(git(&worktree, &["rev-parse", "--abbrev-ref", "HEAD"]), "master");
+    let worktrees = git(&hub, &["worktree", "list", "--porcelain"]);
+    assert!(worktrees.contains(&format!("worktree {}\n", worktree.display())));
+    assert!(!worktrees.contains(&format!("worktree {}/", git_dir.display())));
+
+    assert!(fs::read_to_string(hub.join(".wt.toml")).unwrap().starts_with("# wt configuration"));
+
+    // 之後新增的 worktree 同樣放在 hub 中
+    sandbox.wt(&["-C", hub.to_str().unwrap(), "add", "feature"]);
+    assert!(hub.join("feature").join("README.md").is_file());
+}
+
+#[test]
+fn skips_the_cloned_repository_hooks_unless_trusted() {
+    let sandbox = Sandbox::new("hooks");
+    let url = sandbox.source(&[(".wt.toml", "[hooks]\npost_add = [\"touch HOOK_RAN\"]\n")]);
+
+    let result = sandbox.clone_json(&[&url, "untrusted"]);
+    assert!(!sandbox.dir.join("untrusted").join("HOOK_RAN").exists());
+    assert_eq!(result["skipped"][0], "run 'touch HOOK_RAN'");
+
+    let result = sandbox.clone_json(&["--run-hooks", &url, "trusted"]);
+    assert!(sandbox.dir.join("trusted").join("HOOK_RAN").exists());
+    assert_eq!(result["skipped"].as_array().unwrap().len(), 0);
+}
\ No newline at end of file
@@ -0,0 +1,238 @@
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// 每個測試使用獨立的暫存目錄，內含 master 與 feature 兩個分支的倉庫
+struct Sandbox {
+    dir: PathBuf,
+    repo: PathBuf,
+}
+
+impl Sandbox {
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("wt-doctor-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let dir = dir.canonicalize().unwrap();
+
+        let repo = dir.join("app");
+        fs::create_dir(&repo).unwrap();
+        git(&repo, &["init", "-q", "-b", "master"]);
+        fs::write(repo.join("README.md"), "hello\n").unwrap();
+        git(&repo, &["add", "."]);
+        git(&repo, &["commit", "-q", "-m", "initial"]);
+        git(&repo, &["branch", "feature"]);
+
+        Self { dir, repo }
+    }
+
+    fn wt(&self, args: &[&str]) -> Value {
+        let mut full = vec!["--output", "json"];
+        full.extend(args);
+        let output = Command::new(env!("CARGO_BIN_EXE_wt"))
+            .args(&full)
+            .current_dir(&self.repo)
+            .envs(identity())
+            .env_remove("WT_REPO")
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "wt {:?} failed: {}", full, String::from_utf8_lossy(&output.stderr));
+
+        let value: Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(value["ok"], true);
+        value["result"].clone()
+    }
+
+    // 以 wt add 建立 worktree，回傳其路徑
+    fn add(&self, branch: &str) -> PathBuf {
+        path(&self.wt(&["add", branch])["path"])
+    }
+
+    // 診斷出的問題，不含 kind 以外的欄位時也能比對
+    fn issues(&self) -> Vec<Value> {
+        self.wt(&["doctor"])["issues"].as_array().unwrap().iter()
+            .map(|issue| issue["issue"].clone())
+            .collect()
+    }
+
+    fn issue(&self, kind: &str) -> Option<Value> {
+        self.issues().into_iter().find(|issue| issue["kind"] == kind)
+    }
+
+    // 非互動模式下的 --fix，回傳套用的修復
+    fn fix(&self) -> Vec<Value> {
+        self.wt(&["doctor", "--fix"])["fixed"].as_array().unwrap().clone()
+    }
+
+    fn worktree_root(&self) -> PathBuf {
+        self.dir.join("app-worktree")
+    }
+}
+
+impl Drop for Sandbox {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+fn identity() -> [(&'static str, &'static str); 4] {
+    [
+        ("GIT_AUTHOR_NAME", "wt"),
+        ("GIT_AUTHOR_EMAIL", "wt@example.com"),
+        ("GIT_COMMITTER_NAME", "wt"),
+        ("GIT_COMMITTER_EMAIL", "wt@example.com"),
+    ]
+}
+
+fn git(cwd: &Path, args: &[&str]) -> String {
+    let output = Command::new("git").args(args).current_dir(cwd).envs(identity()).output().unwrap();
+    assert!(output.status.success(), "git {:?} failed: {}", args, String::from_utf8_lossy(&output.stderr));
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+fn path(value: &Value) -> PathBuf {
+    PathBuf::from(value.as_str().unwrap())
+}
+
+// worktree 在 .git/worktrees 中的管理目錄
+fn admin_dir(worktree: &Path) -> PathBuf {
+    let content = fs::read_to_string(worktree.join(".git")).unwrap();
+    PathBuf::from(content.trim().strip_prefix("gitdir: ").unwrap())
+}
+
+#[test]
+fn reports_no_issues_for_a_healthy_repository() {
+    let sandbox = Sandbox::new("healthy");
+    sandbox.add("feature");
+
+    let result = sandbox.wt(&["doctor"]);
+    assert_eq!(result["issues"].as_array().unwrap().len(), 0);
+}
+
+#[test]
+fn prunes_a_missing_directory() {
+    let sandbox = Sandbox::new("missing");
+    let worktree = sandbox.add("feature");
+    fs::remove_dir_all(&worktree).unwrap();
+
+    let issue = sandbox.issue("missing_directory").unwrap();
+    assert_eq!(path(&issue["path"]), worktree);
+    assert_eq!(issue["branch"], "feature");
+
+    let fixed = sandbox.fix();
+    assert_eq!(fixed[0]["fix"], "prune");
+    assert!(sandbox.issue("missing_directory").is_none());
+    assert!(!git(&sandbox.repo, &["worktree", "list"]).contains("[feature]"));
+}
+
+#[test]
+fn deletes_a_stale_admin_entry() {
+    let sandbox = Sandbox::new("stale");
+    let stale = sandbox.repo.join(".git").join("worktrees").join("leftover");
+    fs::create_dir_all(&stale).unwrap();
+
+    let issue = sandbox.issue("stale_admin_entry").unwrap();
+    assert_eq!(path(&issue["admin_dir"]), stale);
+
+    let fixed = sandbox.fix();
+    assert_eq!(fixed[0]["fix"], "delete_admin_entry");
+    assert!(!stale.exists());
+    assert!(sandbox.issue("stale_admin_entry").is_none());
+}
+
+#[test]
+fn repairs_a_broken_link() {
+    let sandbox = Sandbox::new("broken");
+    let worktree = sandbox.add("feature");
+    fs::write(worktree.join(".git"), format!("gitdir: {}\n", sandbox.dir.join("elsewhere").display())).unwrap();
+
+    let issue = sandbox.issue("broken_link").unwrap();
+    assert_eq!(path(&issue["path"]), worktree);
+
+    let fixed = sandbox.fix();
+    assert_eq!(fixed[0]["fix"], "repair");
+    assert!(sandbox.issues().is_empty());
+    assert_eq!(git(&worktree, &["rev-parse", "--abbrev-ref", "HEAD"]), "feature");
+}
+
+#[test]
+fn repairs_a_moved_worktree_and_records_the_move() {
+    let sandbox = Sandbox::new("moved");
+    let worktree = sandbox.add("feature");
+    let moved = sandbox.worktree_root().join("renamed");
+    fs::rename(&worktree, &moved).unwrap();
+
+    // 原本的路徑由 repair 一併修復，不另外列為遺失的目錄
+    let issues = sandbox.issues();
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0]["kind"], "moved_worktree");
+    assert_eq!(path(&issues[0]["path"]), moved);
+
+    let fixed = sandbox.fix();
+    assert_eq!(fixed[0]["fix"], "repair");
+    assert!(sandbox.issues().is_empty());
+    assert!(git(&sandbox.repo, &["worktree", "list", "--porcelain"]).contains(&format!("worktree {}\n", moved.display())));
+
+    let journal = sandbox.wt(&["undo", "--list"]);
+    let entry = journal["entries"].as_array().unwrap().last().unwrap();
+    assert_eq!(entry["operation"], "move");
+    assert_eq!(path(&entry["moved_from"]), worktree);
+    assert_eq!(path(&entry["path"]), moved);
+
+    // wt undo 把 worktree 移回原本的位置
+    sandbox.wt(&["undo"]);
+    assert!(worktree.join("README.md").is_file());
+    assert!(!moved.exists());
+    assert!(sandbox.issues().is_empty());
+}
+
+#[test]
+fn reregisters_an_orphaned_directory_for_a_branch() {
+    let sandbox = Sandbox::new("orphaned");
+    let worktree = sandbox.add("feature");
+    fs::write(worktree.join("notes.txt"), "keep me\n").unwrap();
+    fs::remove_dir_all(admin_dir(&worktree)).unwrap();
+
+    let issue = sandbox.issue("orphaned_directory").unwrap();
+    assert_eq!(path(&issue["path"]), worktree);
+    assert_eq!(issue["branch"], "feature");
+
+    let fixed = sandbox.fix();
+    assert_eq!(fixed[0]["fix"], "reregister");
+    assert!(sandbox.issues().is_empty());
+    assert_eq!(git(&worktree, &["rev-parse", "--abbrev-ref", "HEAD"]), "feature");
+    assert_eq!(fs::read_to_string(worktree.join("notes.txt")).unwrap(), "keep me\n");
+    assert_eq!(git(&worktree, &["status", "--porcelain"]), "?? notes.txt");
+}
+
+#[test]
+fn keeps_an_orphaned_directory_without_a_branch() {
+    let sandbox = Sandbox::new("unknown");
+    let orphan = sandbox.worktree_root().join("unknown");
+    fs::create_dir_all(&orphan).unwrap();
+    fs::write(orphan.join(".git"), format!("gitdir: {}\n", sandbox.dir.join("gone").display())).unwrap();
+
+    let issue = sandbox.issue("orphaned_directory").unwrap();
+    assert_eq!(path(&issue["path"]), orphan);
+    assert_eq!(issue["branch"], Value::Null);
+
+    // 刪除目錄不是安全的修復，非互動模式下不會套用
+    assert!(sandbox.fix().is_empty());
+    assert!(orphan.join(".git").is_file());
+}
+
+#[test]
+fn keeps_an_unused_branch() {
+    let sandbox = Sandbox::new("unused");
+
+    let issues = sandbox.issues();
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0]["kind"], "unused_branch");
+    assert_eq!(issues[0]["branch"], "feature");
+    assert_eq!(issues[0]["merged"], true);
+
+    // 刪除分支不是安全的修復，非互動模式下不會套用
+    assert!(sandbox.fix().is_empty());
+    git(&sandbox.repo, &["rev-parse", "--verify", "refs/heads/feature"]);
+}
\ No newline at end of file
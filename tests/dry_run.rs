@@ -0,0 +1,161 @@
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// 每個測試使用獨立的暫存目錄，內含 master 與 feature 兩個分支的倉庫，feature 已有 worktree
+struct Sandbox {
+    dir: PathBuf,
+    repo: PathBuf,
+}
+
+impl Sandbox {
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("wt-dry-run-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let dir = dir.canonicalize().unwrap();
+
+        let repo = dir.join("app");
+        fs::create_dir(&repo).unwrap();
+        git(&repo, &["init", "-q", "-b", "master"]);
+        fs::write(repo.join("README.md"), "hello\n").unwrap();
+        git(&repo, &["add", "."]);
+        git(&repo, &["commit", "-q", "-m", "initial"]);
+        git(&repo, &["branch", "feature"]);
+        git(&repo, &["worktree", "add", "-q", "../feature", "feature"]);
+
+        Self { dir, repo }
+    }
+
+    // 以 --dry-run --output json 執行，回傳計畫與結果
+    fn dry_run(&self, args: &[&str]) -> (Vec<Value>, Value) {
+        let mut full = vec!["--dry-run", "--output", "json"];
+        full.extend(args);
+        let output = Command::new(env!("CARGO_BIN_EXE_wt"))
+            .args(&full)
+            .current_dir(&self.dir)
+            .envs(identity())
+            .env_remove("WT_REPO")
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "wt {:?} failed: {}", full, String::from_utf8_lossy(&output.stderr));
+
+        let value: Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(value["ok"], true);
+        assert_eq!(value["dry_run"], true);
+        (value["plan"].as_array().unwrap().clone(), value["result"].clone())
+    }
+
+    // 目前的所有 ref、worktree 與暫存目錄中的項目，用來確認乾跑沒有留下任何變更
+    fn snapshot(&self) -> (String, String, Vec<PathBuf>) {
+        let refs = git(&self.repo, &["for-each-ref", "--format=%(refname) %(objectname)"]);
+        let worktrees = git(&self.repo, &["worktree", "list", "--porcelain"]);
+        let mut entries: Vec<PathBuf> = fs::read_dir(&self.dir).unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        entries.sort();
+        (refs, worktrees, entries)
+    }
+
+    fn repo_arg(&self) -> &str {
+        self.repo.to_str().unwrap()
+    }
+}
+
+impl Drop for Sandbox {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+fn identity() -> [(&'static str, &'static str); 4] {
+    [
+        ("GIT_AUTHOR_NAME", "wt"),
+        ("GIT_AUTHOR_EMAIL", "wt@example.com"),
+        ("GIT_COMMITTER_NAME", "wt"),
+        ("GIT_COMMITTER_EMAIL", "wt@example.com"),
+    ]
+}
+
+fn git(cwd: &Path, args: &[&str]) -> String {
+    let output = Command::new("git").args(args).current_dir(cwd).envs(identity()).output().unwrap();
+    assert!(output.status.success(), "git {:?} failed: {}", args, String::from_utf8_lossy(&output.stderr));
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+fn action(action: &Value) -> (&str, PathBuf) {
+    (action["action"].as_str().unwrap(), PathBuf::from(action["path"].as_str().unwrap_or_default()))
+}
+
+#[test]
+fn plans_add_without_creating_anything() {
+    let sandbox = Sandbox::new("add");
+    let before = sandbox.snapshot();
+
+    let (plan, result) = sandbox.dry_run(&["-C", sandbox.repo_arg(), "add", "master", "-b", "topic"]);
+    let worktree_parent = sandbox.dir.join("app-worktree");
+    let worktree = worktree_parent.join("app-topic-worktree");
+
+    assert_eq!(plan.len(), 3);
+    assert_eq!(action(&plan[0]), ("create_dir", worktree_parent.clone()));
+    assert_eq!(plan[1]["action"], "create_branch");
+    assert_eq!(plan[1]["branch"], "topic");
+    assert_eq!(plan[1]["from"], "master");
+    assert_eq!(action(&plan[2]), ("add_worktree", worktree.clone()));
+    assert_eq!(plan[2]["reference"], "topic");
+    assert_eq!(plan[2]["detached"], false);
+    assert_eq!(PathBuf::from(result["path"].as_str().unwrap()), worktree);
+
+    assert!(!worktree_parent.exists());
+    assert_eq!(sandbox.snapshot(), before);
+}
+
+#[test]
+fn plans_remove_without_removing_anything() {
+    let sandbox = Sandbox::new("remove");
+    let before = sandbox.snapshot();
+
+    let (plan, result) = sandbox.dry_run(&["-C", sandbox.repo_arg(), "remove", "feature"]);
+    let worktree = sandbox.dir.join("feature");
+
+    assert_eq!(plan.len(), 1);
+    assert_eq!(action(&plan[0]), ("remove_worktree", worktree.clone()));
+    assert_eq!(result["removed"][0]["branch"], "feature");
+
+    assert!(worktree.join("README.md").is_file());
+    assert_eq!(sandbox.snapshot(), before);
+}
+
+#[test]
+fn plans_clone_without_cloning() {
+    let sandbox = Sandbox::new("clone");
+    let url = format!("file://{}", sandbox.repo.display());
+    let before = sandbox.snapshot();
+
+    let (plan, result) = sandbox.dry_run(&["clone", &url, "copy"]);
+    let target = sandbox.dir.join("copy");
+    let actions: Vec<(&str, PathBuf)> = plan.iter().map(action).collect();
+    assert_eq!(actions, [
+        ("clone", target.clone()),
+        ("configure_fetch", target.clone()),
+        ("write_file", target.join(".wt.toml")),
+    ]);
+    assert_eq!(plan[0]["url"], url.as_str());
+    assert_eq!(result["default_branch"], "master");
+
+    let (plan, result) = sandbox.dry_run(&["clone", "--bare-hub", &url, "hub"]);
+    let hub = sandbox.dir.join("hub");
+    let actions: Vec<(&str, PathBuf)> = plan.iter().map(action).collect();
+    assert_eq!(actions, [
+        ("clone", hub.clone()),
+        ("configure_fetch", hub.join(".bare")),
+        ("write_file", hub.join(".wt.toml")),
+        ("add_worktree", hub.join("master")),
+    ]);
+    assert_eq!(PathBuf::from(result["default_worktree"].as_str().unwrap()), hub.join("master"));
+
+    assert!(!target.exists());
+    assert!(!hub.exists());
+    assert_eq!(sandbox.snapshot(), before);
+}
\ No newline at end of file
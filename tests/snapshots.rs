@@ -0,0 +1,177 @@
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// 每個測試使用獨立的暫存目錄，內含 master 與 feature 兩個分支的倉庫，feature 已有 worktree
+struct Sandbox {
+    dir: PathBuf,
+    repo: PathBuf,
+    worktree: PathBuf,
+}
+
+impl Sandbox {
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("wt-snapshots-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let dir = dir.canonicalize().unwrap();
+
+        let repo = dir.join("app");
+        fs::create_dir(&repo).unwrap();
+        git(&repo, &["init", "-q", "-b", "master"]);
+        fs::write(repo.join("README.md"), "hello\n").unwrap();
+        fs::write(repo.join("notes.txt"), "notes\n").unwrap();
+        fs::write(repo.join(".gitignore"), "*.log\n").unwrap();
+        git(&repo, &["add", "."]);
+        git(&repo, &["commit", "-q", "-m", "initial"]);
+        git(&repo, &["branch", "feature"]);
+
+        let worktree = dir.join("feature");
+        git(&repo, &["worktree", "add", "-q", worktree.to_str().unwrap(), "feature"]);
+
+        Self { dir, repo, worktree }
+    }
+
+    // 已暫存、未暫存、未追蹤與被忽略的變更各一
+    fn make_changes(&self) {
+        fs::write(self.worktree.join("README.md"), "staged\n").unwrap();
+        git(&self.worktree, &["add", "README.md"]);
+        fs::write(self.worktree.join("notes.txt"), "unstaged\n").unwrap();
+        fs::write(self.worktree.join("new.txt"), "untracked\n").unwrap();
+        fs::write(self.worktree.join("debug.log"), "ignored\n").unwrap();
+    }
+
+    fn wt(&self, args: &[&str]) -> Value {
+        let mut full = vec!["--output", "json"];
+        full.extend(args);
+        let output = Command::new(env!("CARGO_BIN_EXE_wt"))
+            .args(&full)
+            .current_dir(&self.repo)
+            .envs(identity())
+            .env_remove("WT_REPO")
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "wt {:?} failed: {}", full, String::from_utf8_lossy(&output.stderr));
+
+        let value: Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(value["ok"], true);
+        value["result"].clone()
+    }
+}
+
+impl Drop for Sandbox {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+fn identity() -> [(&'static str, &'static str); 4] {
+    [
+        ("GIT_AUTHOR_NAME", "wt"),
+        ("GIT_AUTHOR_EMAIL", "wt@example.com"),
+        ("GIT_COMMITTER_NAME", "wt"),
+        ("GIT_COMMITTER_EMAIL", "wt@example.com"),
+    ]
+}
+
+fn git(cwd: &Path, args: &[&str]) -> String {
+    let output = Command::new("git").args(args).current_dir(cwd).envs(identity()).output().unwrap();
+    assert!(output.status.success(), "git {:?} failed: {}", args, String::from_utf8_lossy(&output.stderr));
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+}
+
+// 還原後的 worktree 應與移除前的狀態相同；被忽略的檔案不在快照中
+fn assert_changes_restored(worktree: &Path) {
+    assert_eq!(fs::read_to_string(worktree.join("README.md")).unwrap(), "staged\n");
+    assert_eq!(fs::read_to_string(worktree.join("notes.txt")).unwrap(), "unstaged\n");
+    assert_eq!(fs::read_to_string(worktree.join("new.txt")).unwrap(), "untracked\n");
+    assert!(!worktree.join("debug.log").exists());
+    assert_eq!(git(worktree, &["status", "--porcelain"]), "M  README.md\n M notes.txt\n?? new.txt");
+}
+
+#[test]
+fn force_remove_saves_changes_and_undo_restores_them() {
+    let sandbox = Sandbox::new("undo");
+    sandbox.make_changes();
+
+    let before = now();
+    let result = sandbox.wt(&["remove", "--force", "feature"]);
+    let after = now();
+    assert!(!sandbox.worktree.exists());
+
+    let name = result["snapshot"].as_str().unwrap().to_string();
+    let (branch, timestamp) = name.rsplit_once('/').unwrap();
+    assert_eq!(branch, "feature");
+    assert!((before..=after).contains(&timestamp.parse::<u64>().unwrap()));
+
+    let reference = format!("refs/wt/snapshots/{}", name);
+    let commit = git(&sandbox.repo, &["rev-parse", "--verify", &reference]);
+    // 與 git stash 相同的格式：HEAD、索引，以及未追蹤檔案三個 parent
+    assert_eq!(git(&sandbox.repo, &["rev-list", "--parents", "-n", "1", &commit]).split(' ').count(), 4);
+    let untracked = git(&sandbox.repo, &["ls-tree", "--name-only", &format!("{}^3", commit)]);
+    assert_eq!(untracked, "new.txt");
+
+    let listed = sandbox.wt(&["snapshots"]);
+    assert_eq!(listed["snapshots"][0]["name"], name.as_str());
+    assert_eq!(listed["snapshots"][0]["reference"], reference.as_str());
+    assert_eq!(listed["snapshots"][0]["commit"], commit.as_str());
+
+    let journal = sandbox.wt(&["undo", "--list"]);
+    assert_eq!(journal["entries"][0]["snapshot"], name.as_str());
+
+    sandbox.wt(&["undo"]);
+    assert_changes_restored(&sandbox.worktree);
+}
+
+#[test]
+fn applies_a_snapshot_to_another_worktree() {
+    let sandbox = Sandbox::new("apply");
+    sandbox.make_changes();
+
+    let result = sandbox.wt(&["remove", "--force", "feature"]);
+    let name = result["snapshot"].as_str().unwrap();
+
+    git(&sandbox.repo, &["worktree", "add", "-q", sandbox.worktree.to_str().unwrap(), "feature"]);
+    assert_eq!(git(&sandbox.worktree, &["status", "--porcelain"]), "");
+
+    let applied = sandbox.wt(&["snapshots", "apply", name, "feature"]);
+    assert_eq!(applied["applied"]["name"], name);
+    assert_eq!(PathBuf::from(applied["worktree"].as_str().unwrap()), sandbox.worktree);
+    assert_changes_restored(&sandbox.worktree);
+}
+
+#[test]
+fn snapshots_in_the_same_second_get_a_suffix() {
+    let sandbox = Sandbox::new("suffix");
+    sandbox.make_changes();
+
+    // 預先佔用接下來幾秒的快照名稱，讓移除時一定遇到同一秒的既有快照
+    let head = git(&sandbox.repo, &["rev-parse", "master"]);
+    let start = now();
+    for timestamp in start..start + 10 {
+        git(&sandbox.repo, &["update-ref", &format!("refs/wt/snapshots/feature/{}", timestamp), &head]);
+    }
+
+    let result = sandbox.wt(&["remove", "--force", "feature"]);
+    let name = result["snapshot"].as_str().unwrap();
+    let (timestamp, sequence) = name.strip_prefix("feature/").unwrap().split_once('-').unwrap();
+    assert_eq!(sequence, "2");
+
+    // 既有的快照沒有被覆寫，新的快照排在同一秒的快照之後
+    assert_eq!(git(&sandbox.repo, &["rev-parse", &format!("refs/wt/snapshots/feature/{}", timestamp)]), head);
+    let listed = sandbox.wt(&["snapshots"]);
+    let names: Vec<&str> = listed["snapshots"].as_array().unwrap().iter()
+        .map(|snapshot| snapshot["name"].as_str().unwrap())
+        .collect();
+    let position = names.iter().position(|listed| *listed == name).unwrap();
+    assert_eq!(names[position - 1], format!("feature/{}", timestamp));
+
+    sandbox.wt(&["snapshots", "apply", name, "master"]);
+    assert_eq!(fs::read_to_string(sandbox.repo.join("new.txt")).unwrap(), "untracked\n");
+}
\ No newline at end of file
@@ -0,0 +1,158 @@
+use serde_json::Value;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// 每個測試使用獨立的暫存目錄，內含 master 與 feature 兩個分支的倉庫
+struct Sandbox {
+    dir: PathBuf,
+    repo: PathBuf,
+}
+
+impl Sandbox {
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("wt-undo-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let dir = dir.canonicalize().unwrap();
+
+        let repo = dir.join("app");
+        fs::create_dir(&repo).unwrap();
+        git(&repo, &["init", "-q", "-b", "master"]);
+        fs::write(repo.join("README.md"), "hello\n").unwrap();
+        git(&repo, &["add", "."]);
+        git(&repo, &["commit", "-q", "-m", "initial"]);
+        git(&repo, &["branch", "feature"]);
+
+        Self { dir, repo }
+    }
+
+    fn wt(&self, args: &[&str]) -> Value {
+        let mut full = vec!["--output", "json"];
+        full.extend(args);
+        let output = Command::new(env!("CARGO_BIN_EXE_wt"))
+            .args(&full)
+            .current_dir(&self.repo)
+            .envs(identity())
+            .env_remove("WT_REPO")
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "wt {:?} failed: {}", full, String::from_utf8_lossy(&output.stderr));
+
+        let value: Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(value["ok"], true);
+        value["result"].clone()
+    }
+
+    fn journal(&self) -> Vec<Value> {
+        self.wt(&["undo", "--list"])["entries"].as_array().unwrap().clone()
+    }
+
+    fn has_branch(&self, branch: &str) -> bool {
+        Command::new("git")
+            .args(["rev-parse", "--verify", "--quiet", &format!("refs/heads/{}", branch)])
+            .current_dir(&self.repo)
+            .output()
+            .unwrap()
+            .status
+            .success()
+    }
+
+    fn worktree_list(&self) -> String {
+        git(&self.repo, &["worktree", "list", "--porcelain"])
+    }
+}
+
+impl Drop for Sandbox {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+fn identity() -> [(&'static str, &'static str); 4] {
+    [
+        ("GIT_AUTHOR_NAME", "wt"),
+        ("GIT_AUTHOR_EMAIL", "wt@example.com"),
+        ("GIT_COMMITTER_NAME", "wt"),
+        ("GIT_COMMITTER_EMAIL", "wt@example.com"),
+    ]
+}
+
+fn git(cwd: &Path, args: &[&str]) -> String {
+    let output = Command::new("git").args(args).current_dir(cwd).envs(identity()).output().unwrap();
+    assert!(output.status.success(), "git {:?} failed: {}", args, String::from_utf8_lossy(&output.stderr));
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+fn path(value: &Value) -> PathBuf {
+    PathBuf::from(value.as_str().unwrap())
+}
+
+#[test]
+fn undoes_an_add_and_deletes_the_created_branch() {
+    let sandbox = Sandbox::new("add");
+
+    let worktree = path(&sandbox.wt(&["add", "master", "-b", "topic"])["path"]);
+    assert!(worktree.join("README.md").is_file());
+
+    let entries = sandbox.journal();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["operation"], "add");
+    assert_eq!(entries[0]["branch"], "topic");
+    assert_eq!(entries[0]["created_branch"], true);
+    assert_eq!(path(&entries[0]["path"]), worktree);
+
+    let result = sandbox.wt(&["undo"]);
+    assert_eq!(result["undone"]["id"], 1);
+    assert!(!worktree.exists());
+    assert!(!sandbox.worktree_list().contains("topic"));
+    assert!(!sandbox.has_branch("topic"));
+
+    assert_eq!(sandbox.journal()[0]["undone"], true);
+}
+
+#[test]
+fn keeps_a_created_branch_with_new_commits() {
+    let sandbox = Sandbox::new("keep");
+
+    let worktree = path(&sandbox.wt(&["add", "master", "-b", "topic"])["path"]);
+    fs::write(worktree.join("notes.txt"), "work\n").unwrap();
+    git(&worktree, &["add", "."]);
+    git(&worktree, &["commit", "-q", "-m", "work"]);
+
+    sandbox.wt(&["undo"]);
+    assert!(!worktree.exists());
+    assert!(sandbox.has_branch("topic"));
+}
+
+#[test]
+fn restores_a_removed_worktree_and_its_branch() {
+    let sandbox = Sandbox::new("restore");
+
+    let worktree = path(&sandbox.wt(&["add", "feature"])["path"]);
+    let entry = sandbox.journal()[0].clone();
+    assert_eq!(entry["created_branch"], false);
+
+    // 模擬移除：刪除 worktree 與分支，再補上一筆移除紀錄
+    git(&sandbox.repo, &["worktree", "remove", worktree.to_str().unwrap()]);
+    git(&sandbox.repo, &["branch", "-D", "feature"]);
+    let mut removal = entry.clone();
+    removal["id"] = 2.into();
+    removal["operation"] = "remove".into();
+    let journal = PathBuf::from(git(&sandbox.repo, &["rev-parse", "--git-common-dir"]));
+    let mut file = OpenOptions::new().append(true).open(sandbox.repo.join(journal).join("wt-journal.jsonl")).unwrap();
+    writeln!(file, "{}", removal).unwrap();
+
+    let result = sandbox.wt(&["restore", "2"]);
+    assert_eq!(result["restored"]["id"], 2);
+    assert!(worktree.join("README.md").is_file());
+    assert_eq!(git(&worktree, &["rev-parse", "--abbrev-ref", "HEAD"]), "feature");
+    assert_eq!(git(&worktree, &["rev-parse", "HEAD"]), entry["commit"].as_str().unwrap());
+
+    // 已還原的紀錄不能再還原，也不會被 wt undo 重複處理
+    let journal = sandbox.journal();
+    assert_eq!(journal[1]["undone"], true);
+    assert_eq!(sandbox.wt(&["undo"])["undone"]["id"], 1);
+    assert!(!worktree.exists());
+}
\ No newline at end of file